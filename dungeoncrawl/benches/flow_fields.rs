@@ -0,0 +1,64 @@
+// compares building the chase and flee maps every enemy turn with the shared flow fields
+// run with cargo bench --bench flow_fields, no harness so it runs on stable
+use dungeoncrawl::prelude::*;
+use std::time::{Duration, Instant};
+
+const TURNS: i32 = 200;
+// the player stands still for most turns, fighting, picking things up or waiting
+const MOVES_EVERY: i32 = 4;
+
+// open floor broken up by pillars so the searches have to go round things
+fn large_map(size: i32) -> Map {
+    let mut map = Map::new(size, size);
+    for y in (2..size - 2).step_by(4) {
+        for x in (2..size - 2).step_by(4) {
+            let idx = map.point2d_to_index(Point::new(x, y));
+            map.tiles[idx] = TileType::Wall;
+        }
+    }
+    map
+}
+
+fn player_at(turn: i32, size: i32) -> Point {
+    let step = turn / MOVES_EVERY;
+    Point::new(1 + step % (size - 2), size / 2)
+}
+
+// what chasing and flee did before, a fresh map each for every enemy turn
+fn per_turn(map: &Map) -> Duration {
+    let start = Instant::now();
+    for turn in 0..TURNS {
+        let player = map.point2d_to_index(player_at(turn, map.width));
+        let chase = DijkstraMap::new(map.width, map.height, &[player], map, 1024.0);
+        let flee = DijkstraMap::new(map.width, map.height, &[player], map, 1024.0);
+        std::hint::black_box((chase, flee));
+    }
+    start.elapsed()
+}
+
+fn cached(map: &Map) -> (Duration, usize) {
+    let mut fields = FlowFields::default();
+    let start = Instant::now();
+    for turn in 0..TURNS {
+        let player = player_at(turn, map.width);
+        std::hint::black_box(fields.toward(Goal::Player, &[player], map));
+        std::hint::black_box(fields.safety(player, map));
+    }
+    (start.elapsed(), fields.rebuilds())
+}
+
+fn main() {
+    for size in [80, 160, 320] {
+        let map = large_map(size);
+        let before = per_turn(&map);
+        let (after, rebuilds) = cached(&map);
+        println!(
+            "{size}x{size}: per turn {:>8.1?} ({} builds), flow fields {:>8.1?} ({} builds), {:.1}x",
+            before,
+            TURNS * 2,
+            after,
+            rebuilds,
+            before.as_secs_f64() / after.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// everything the player can ask for on their turn, whichever way the input arrives
+// (keyboard in the window, a script when running headless)
+// serializable so accepted actions can be recorded to a replay
+// keys are bound to actions in resources/keymap.ron
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Action {
+    Move(Point),
+    Pickup,
+    Use(usize), // index into the carried item list
+    CloseDoor,  // every open door next to the player
+    Wait,
+    Descend, // take the stairs down when standing on them
+    Save,
+    Load,
+    // screens drawn by the front end, they never reach the rules systems
+    Inventory,
+    Help,
+    Restart,
+    AiDebug, // shows what the monsters are thinking
+    // only while a replay is playing
+    ReplayPause,
+    ReplayStep,
+    ReplayFastForward,
+}
+
+impl Action {
+    // what the action does, for the help screen and keymap errors
+    pub fn describe(&self) -> String {
+        match self {
+            Action::Move(delta) => {
+                let vertical = match delta.y {
+                    -1 => "north",
+                    1 => "south",
+                    _ => "",
+                };
+                let horizontal = match delta.x {
+                    -1 => "west",
+                    1 => "east",
+                    _ => "",
+                };
+                format!("Move {}{}", vertical, horizontal)
+            }
+            Action::Pickup => "Pick up".to_string(),
+            Action::Use(index) => format!("Use item {}", index + 1),
+            Action::CloseDoor => "Close doors".to_string(),
+            Action::Wait => "Wait a turn".to_string(),
+            Action::Descend => "Go down the stairs".to_string(),
+            Action::Save => "Save".to_string(),
+            Action::Load => "Load".to_string(),
+            Action::Inventory => "Inventory".to_string(),
+            Action::Help => "Help".to_string(),
+            Action::Restart => "New game".to_string(),
+            Action::AiDebug => "Monster AI overlay".to_string(),
+            Action::ReplayPause => "Pause a replay".to_string(),
+            Action::ReplayStep => "Step a paused replay".to_string(),
+            Action::ReplayFastForward => "Fast forward a replay".to_string(),
+        }
+    }
+}
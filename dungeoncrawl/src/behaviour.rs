@@ -0,0 +1,157 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// how far from where the player was last seen a search looks around
+pub const SEARCH_RADIUS: i32 = 3;
+
+fn default_search_turns() -> i32 {
+    10
+}
+
+// what a monster does while it can't see the player, set per template in template.ron
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Calm {
+    Idle,                   // waits where it is
+    Wander,                 // random steps
+    Patrol { radius: i32 }, // walks between points around where it spawned
+    Guard { leash: i32 },   // stays at its post, only chases players within leash of it
+}
+
+// a monster's temperament, leave it out of a template for one that waits and chases on sight
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Behaviour {
+    pub calm: Calm,
+    // runs from the player at or below this percent of its health, 0 never runs
+    #[serde(default)]
+    pub flee_below: i32,
+    // turns spent looking around where the player was last seen before giving up
+    #[serde(default = "default_search_turns")]
+    pub search_turns: i32,
+}
+
+impl Default for Behaviour {
+    fn default() -> Self {
+        Self {
+            calm: Calm::Idle,
+            flee_below: 0,
+            search_turns: default_search_turns(),
+        }
+    }
+}
+
+// what the monster is doing right now
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AiState {
+    Idle,
+    Wander,
+    Patrol(Point), // walking to this point
+    Chase,
+    Search, // lost the player, heading for where it was last seen and looking around
+    Flee,
+    Guard, // back to its post and wait there
+}
+
+// the state machine for one monster, moved along by the ai system each enemy turn
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Brain {
+    pub behaviour: Behaviour,
+    pub state: AiState,
+    pub home: Point, // where it spawned, patrols and guard posts are around it
+}
+
+impl AiState {
+    // short enough to print over a monster
+    pub fn name(&self) -> &'static str {
+        match self {
+            AiState::Idle => "idle",
+            AiState::Wander => "wander",
+            AiState::Patrol(_) => "patrol",
+            AiState::Chase => "chase",
+            AiState::Search => "search",
+            AiState::Flee => "flee",
+            AiState::Guard => "guard",
+        }
+    }
+}
+
+impl Brain {
+    pub fn new(behaviour: Behaviour, home: Point) -> Self {
+        let mut brain = Self {
+            behaviour,
+            state: AiState::Idle,
+            home,
+        };
+        brain.state = brain.calm_state();
+        brain
+    }
+
+    fn calm_state(&self) -> AiState {
+        match self.behaviour.calm {
+            Calm::Idle => AiState::Idle,
+            Calm::Wander => AiState::Wander,
+            Calm::Patrol { .. } => AiState::Patrol(self.home),
+            Calm::Guard { .. } => AiState::Guard,
+        }
+    }
+
+    pub fn is_wounded(&self, health: &Health) -> bool {
+        health.current * 100 <= health.max * self.behaviour.flee_below
+    }
+
+    // guards leave players beyond their leash alone
+    fn within_leash(&self, p: Point) -> bool {
+        match self.behaviour.calm {
+            Calm::Guard { leash } => {
+                DistanceAlg::Pythagoras.distance2d(self.home, p) <= leash as f32
+            }
+            _ => true,
+        }
+    }
+
+    // picks the next state from what the monster can see, remembers and how hurt it is
+    // player is where the player stands if it is in the monster's field of view
+    // last_seen is where it remembers the player, while it still has turns left to search
+    pub fn think(
+        &mut self,
+        player: Option<Point>,
+        last_seen: Option<Point>,
+        health: Option<&Health>,
+    ) {
+        let hunting = matches!(self.state, AiState::Chase | AiState::Search);
+        self.state = match (player, last_seen) {
+            (Some(_), _) if health.is_some_and(|h| self.is_wounded(h)) => AiState::Flee,
+            (Some(p), _) if self.within_leash(p) => AiState::Chase,
+            (None, Some(p)) if hunting && self.within_leash(p) => AiState::Search,
+            _ => match self.state {
+                AiState::Chase | AiState::Search | AiState::Flee => self.calm_state(),
+                // carry on with the patrol rather than start it over
+                state => state,
+            },
+        };
+    }
+
+    // where a patrolling or guarding monster is walking to
+    pub fn destination(&self) -> Option<Point> {
+        match self.state {
+            AiState::Patrol(target) => Some(target),
+            AiState::Guard => Some(self.home),
+            _ => None,
+        }
+    }
+
+    // another open tile around home, None if the rolls all landed on walls
+    pub fn next_patrol_point(&self, map: &Map, rng: &mut RandomNumberGenerator) -> Option<Point> {
+        let Calm::Patrol { radius } = self.behaviour.calm else {
+            return None;
+        };
+        (0..10)
+            .map(|_| {
+                self.home
+                    + Point::new(
+                        rng.range(-radius, radius + 1),
+                        rng.range(-radius, radius + 1),
+                    )
+            })
+            .find(|p| map.can_enter_tile(*p))
+    }
+}
@@ -0,0 +1,123 @@
+use crate::prelude::*;
+use crate::spawner::open_resource;
+use ron::de::from_reader;
+use serde::Deserialize;
+
+// every architect can build a playable map this small, Campaign::check refuses anything smaller
+pub const MIN_FLOOR_SIZE: i32 = 8;
+
+// how one floor of the dungeon is generated
+#[derive(Deserialize, Clone, Debug)]
+pub struct Floor {
+    // depth of the floor, filled in by Campaign::floor rather than read from the file
+    #[serde(skip)]
+    pub level: u32,
+    pub width: i32,
+    pub height: i32,
+    // architect names with their share of the mix, picked when the floor is first visited
+    pub architects: Vec<(String, u32)>,
+    pub themes: Vec<String>,
+    // scales the number of monsters and items the architect places, 1.0 keeps them as is
+    pub monster_density: f32,
+    // steps run on the architect's map in order, placing the prefab when not given
+    #[serde(default = "default_post_processing")]
+    pub post_processing: Vec<PostStep>,
+}
+
+impl Floor {
+    // any architect and theme, used when no campaign is involved
+    pub fn any(width: i32, height: i32) -> Self {
+        Self {
+            level: 0,
+            width,
+            height,
+            architects: ArchitectRegistry::standard().default_weights(),
+            themes: THEME_NAMES.iter().map(|t| t.to_string()).collect(),
+            monster_density: 1.0,
+            post_processing: default_post_processing(),
+        }
+    }
+}
+
+// the shape of a whole run, loaded from resources/campaign.ron
+#[derive(Deserialize, Clone, Debug)]
+pub struct Campaign {
+    // floors below the grail floor are never reached
+    pub grail_floor: u32,
+    pub floors: Vec<Floor>,
+    // build every floor with this architect, --architect sets it from the command line
+    #[serde(default)]
+    pub architect: Option<String>,
+    // eight way movement for the player and monsters, --diagonal turns it on from the command line
+    #[serde(default)]
+    pub diagonal_movement: bool,
+}
+
+impl Campaign {
+    pub fn load() -> Self {
+        let file =
+            open_resource("resources/campaign.ron").expect("Failed to open resources/campaign.ron");
+        let campaign: Self = from_reader(file).expect("Failed to parse campaign.ron");
+        if let Err(e) = campaign.check() {
+            panic!("Invalid campaign.ron: {}", e);
+        }
+        campaign
+    }
+
+    // catch designer mistakes when the file is loaded, not when the floor is reached
+    pub fn check(&self) -> Result<(), String> {
+        let registry = ArchitectRegistry::standard();
+        if let Some(a) = self.architect.as_ref().filter(|a| !registry.contains(a)) {
+            return Err(format!(
+                "unknown architect {}, expected one of {}",
+                a,
+                registry.names().join(", ")
+            ));
+        }
+        if self.grail_floor as usize >= self.floors.len() {
+            return Err(format!(
+                "grail_floor {} but only {} floors",
+                self.grail_floor,
+                self.floors.len()
+            ));
+        }
+        for (level, floor) in self.floors.iter().enumerate() {
+            if floor.width < MIN_FLOOR_SIZE || floor.height < MIN_FLOOR_SIZE {
+                return Err(format!(
+                    "floor {} is {}x{}, floors are at least {}x{}",
+                    level, floor.width, floor.height, MIN_FLOOR_SIZE, MIN_FLOOR_SIZE
+                ));
+            }
+            if floor.architects.is_empty() || floor.themes.is_empty() {
+                return Err(format!("floor {} needs an architect and a theme", level));
+            }
+            if let Some((a, _)) = floor.architects.iter().find(|(a, _)| !registry.contains(a)) {
+                return Err(format!("floor {} has unknown architect {}", level, a));
+            }
+            if floor.architects.iter().all(|(_, weight)| *weight == 0) {
+                return Err(format!("floor {} has no architect with a weight", level));
+            }
+            if let Some(t) = floor.themes.iter().find(|t| theme_by_name(t).is_none()) {
+                return Err(format!("floor {} has unknown theme {}", level, t));
+            }
+            if floor.monster_density < 0.0 {
+                return Err(format!("floor {} has negative monster density", level));
+            }
+        }
+        Ok(())
+    }
+
+    // the floor as it will be built, with any forced architect applied
+    pub fn floor(&self, map_level: u32) -> Floor {
+        let mut floor = self.floors[map_level as usize].clone();
+        floor.level = map_level;
+        if let Some(architect) = &self.architect {
+            floor.architects = vec![(architect.clone(), 1)];
+        }
+        floor
+    }
+
+    pub fn is_grail_floor(&self, map_level: u32) -> bool {
+        map_level == self.grail_floor
+    }
+}
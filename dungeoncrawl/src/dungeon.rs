@@ -0,0 +1,28 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// a floor the player has left, kept exactly as it was
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StoredLevel {
+    pub map: Map,
+    pub theme: String,
+    pub entities: Vec<SavedEntity>, // monsters and items lying on the floor
+}
+
+// every visited floor except the current one, keyed by map level
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct DungeonLevels {
+    levels: HashMap<u32, StoredLevel>,
+}
+
+impl DungeonLevels {
+    pub fn store(&mut self, level: u32, stored: StoredLevel) {
+        self.levels.insert(level, stored);
+    }
+
+    // removes the floor from the store, it becomes the live level again
+    pub fn take(&mut self, level: u32) -> Option<StoredLevel> {
+        self.levels.remove(&level)
+    }
+}
@@ -0,0 +1,63 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// what one ordinary action takes, something with NORMAL_SPEED gets one of these a round
+pub const ACTION_COST: i32 = 100;
+pub const NORMAL_SPEED: i32 = 100;
+// attacks hitting this hard or harder take longer to recover from
+pub const HEAVY_DAMAGE: i32 = 5;
+pub const HEAVY_ATTACK_COST: i32 = 150;
+
+pub fn default_speed() -> i32 {
+    NORMAL_SPEED
+}
+
+pub fn attack_cost(damage: i32) -> i32 {
+    if damage >= HEAVY_DAMAGE {
+        HEAVY_ATTACK_COST
+    } else {
+        ACTION_COST
+    }
+}
+
+// gained every round by speed and spent acting, a speed of 200 acts twice a round, 50 every other
+// the player and every monster start a floor ready to act
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Energy {
+    pub speed: i32,
+    pub current: i32,
+}
+
+// a monster sitting out this pass of the enemy turn, short of energy
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Recovering;
+
+impl Energy {
+    pub fn new(speed: i32) -> Self {
+        Self {
+            speed,
+            current: ACTION_COST,
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.current >= ACTION_COST
+    }
+
+    // anything ready spends before the round ends, so energy never piles up
+    pub fn gain(&mut self) {
+        self.current += self.speed;
+    }
+
+    // can go below zero, a heavy attack is paid off over the next rounds
+    pub fn spend(&mut self, cost: i32) {
+        self.current -= cost;
+    }
+}
+
+// no monster has the energy for another pass, so this enemy turn ends the round
+pub fn round_is_over(ecs: &SubWorld) -> bool {
+    !<(&Energy, Option<&Player>)>::query()
+        .iter(ecs)
+        .any(|(energy, player)| player.is_none() && energy.is_ready())
+}
@@ -0,0 +1,116 @@
+use crate::prelude::*;
+use std::collections::HashMap;
+
+// how far a path search spreads out from its goals
+const FLOW_DEPTH: f32 = 1024.0;
+// a fleeing monster heads for tiles at least this many steps from the player
+pub const SAFE_DISTANCE: f32 = 10.0;
+// fields kept for single tiles before they are all dropped, every patrol point makes a new one
+const MAX_TILE_FIELDS: usize = 32;
+
+// what a flow field leads towards, walk downhill on it to get there
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Goal {
+    Player,
+    Exits,
+    Items,
+    Safety,      // away from the player, see FlowFields::safety
+    Tile(Point), // one fixed spot, a guard's post or where the player was last seen
+}
+
+// one cached field plus everything it was built from
+struct FlowField {
+    starts: Vec<usize>,
+    width: i32,
+    tiles: Vec<TileType>,
+    diagonal: bool,
+    dijkstra: DijkstraMap,
+}
+
+impl FlowField {
+    fn built_from(&self, starts: &[usize], map: &Map) -> bool {
+        self.starts == starts
+            && self.width == map.width
+            && self.diagonal == map.diagonal
+            && self.tiles == map.tiles
+    }
+}
+
+// distance maps shared by every monster, a resource so the ai systems stop building their own
+// a field is only rebuilt when its goals move or the map's tiles change
+#[derive(Default)]
+pub struct FlowFields {
+    fields: HashMap<Goal, FlowField>,
+    rebuilds: usize,
+}
+
+impl FlowFields {
+    // the field leading to the nearest of targets, targets off the map are left out
+    pub fn toward(&mut self, goal: Goal, targets: &[Point], map: &Map) -> &DijkstraMap {
+        let starts: Vec<usize> = targets.iter().filter_map(|p| map.try_idx(*p)).collect();
+        let fresh = self
+            .fields
+            .get(&goal)
+            .is_some_and(|field| field.built_from(&starts, map));
+        if !fresh {
+            let tile_fields = self
+                .fields
+                .keys()
+                .filter(|g| matches!(g, Goal::Tile(_)))
+                .count();
+            if matches!(goal, Goal::Tile(_)) && tile_fields >= MAX_TILE_FIELDS {
+                self.fields.retain(|g, _| !matches!(g, Goal::Tile(_)));
+            }
+            self.rebuilds += 1;
+            let mut dijkstra = DijkstraMap::new(map.width, map.height, &starts, map, FLOW_DEPTH);
+            // bracket-lib leaves the goals themselves at 2.0, a goal is no steps away
+            starts.iter().for_each(|idx| dijkstra.map[*idx] = 0.0);
+            self.fields.insert(
+                goal,
+                FlowField {
+                    starts,
+                    width: map.width,
+                    tiles: map.tiles.clone(),
+                    diagonal: map.diagonal,
+                    dijkstra,
+                },
+            );
+        }
+        &self.fields[&goal].dijkstra
+    }
+
+    // leads to the tiles at least SAFE_DISTANCE from the player, or the furthest it can reach
+    // going downhill on it takes the way round the player rather than backing into a corner
+    pub fn safety(&mut self, player: Point, map: &Map) -> &DijkstraMap {
+        let from_player = &self.toward(Goal::Player, &[player], map).map;
+        let furthest = from_player
+            .iter()
+            .filter(|d| **d < f32::MAX)
+            .fold(0.0, |a: f32, b| a.max(*b));
+        let safe_at = furthest.min(SAFE_DISTANCE);
+        let safe: Vec<Point> = from_player
+            .iter()
+            .enumerate()
+            .filter(|(_, d)| **d < f32::MAX && **d >= safe_at)
+            .map(|(idx, _)| map.index_to_point2d(idx))
+            .collect();
+        self.toward(Goal::Safety, &safe, map)
+    }
+
+    // the next step downhill from `from` to a single tile, None once there or if it can't be reached
+    pub fn step_toward(&mut self, target: Point, from: Point, map: &Map) -> Option<Point> {
+        let field = self.toward(Goal::Tile(target), &[target], map);
+        let idx = map.try_idx(from)?;
+        map.get_available_exits(idx)
+            .iter()
+            .map(|(exit, _)| *exit)
+            .filter(|exit| field.map[*exit] < field.map[idx])
+            .min_by(|a, b| field.map[*a].total_cmp(&field.map[*b]))
+            .map(|exit| map.index_to_point2d(exit))
+    }
+
+    // how many fields have been built since the resource was made
+    pub fn rebuilds(&self) -> usize {
+        self.rebuilds
+    }
+}
@@ -0,0 +1,322 @@
+use crate::prelude::*;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+// the rules of a run without any window attached
+// State drives this from bracket-lib, the headless runner drives it from a script
+pub struct Game {
+    pub ecs: World,
+    pub resources: Resources,
+    input_systems: Schedule,
+    player_systems: Schedule,
+    enemy_systems: Schedule,
+    fixed_seed: Option<GameSeed>, // from --seed, reused when starting again
+    campaign: Campaign,
+    prefabs: Arc<Prefabs>, // read from prefabs.ron once, shared by every floor built
+    pub recording: Option<Replay>, // None once the run can no longer be replayed
+}
+
+impl Game {
+    pub fn new(fixed_seed: Option<GameSeed>) -> Self {
+        Self::with_campaign(fixed_seed, Campaign::load())
+    }
+
+    pub fn with_campaign(fixed_seed: Option<GameSeed>, campaign: Campaign) -> Self {
+        let mut game = Self {
+            ecs: World::default(),
+            resources: Resources::default(),
+            input_systems: build_input_scheduler(),
+            player_systems: build_player_scheduler(),
+            enemy_systems: build_enemy_scheduler(),
+            fixed_seed,
+            campaign,
+            prefabs: Arc::new(Prefabs::load()),
+            recording: None,
+        };
+        game.reset_game_state();
+        game
+    }
+
+    pub fn reset_game_state(&mut self) {
+        self.ecs = World::default();
+        self.resources = Resources::default();
+        let seed = self.fixed_seed.unwrap_or_else(GameSeed::random);
+        self.resources.insert(seed);
+        // before the first floor is built, it may have something to report
+        self.resources.insert(MessageLog::default());
+        // spawned first so entity order stays the same, moved once the map exists
+        spawn_player(&mut self.ecs, Point::zero());
+        let map_builder = self.build_level(0);
+        <&mut Point>::query()
+            .filter(component::<Player>())
+            .iter_mut(&mut self.ecs)
+            .for_each(|pos| *pos = map_builder.player_start);
+        self.resources
+            .insert(Camera::new(map_builder.player_start, &map_builder.map));
+        self.resources.insert(map_builder.map);
+        self.resources.insert(TurnState::AwaitingInput);
+        self.resources.insert(map_builder.theme);
+        self.resources.insert(DungeonLevels::default());
+        self.resources.insert(seed.ai_rng());
+        self.resources.insert(FlowFields::default());
+        self.resources.insert(Occupancy::default());
+        self.resources.insert(None::<Action>);
+        self.recording = Some(Replay::new(seed, &self.campaign));
+    }
+
+    pub fn turn_state(&self) -> TurnState {
+        *self.resources.get::<TurnState>().unwrap()
+    }
+
+    pub fn campaign(&self) -> &Campaign {
+        &self.campaign
+    }
+
+    pub fn seed(&self) -> GameSeed {
+        *self.resources.get::<GameSeed>().unwrap()
+    }
+
+    // runs one step of the turn state machine
+    // action is only looked at while awaiting input
+    pub fn tick(&mut self, action: Option<Action>) {
+        self.resources.insert(action);
+        let before = self.turn_state();
+        match before {
+            TurnState::AwaitingInput => self
+                .input_systems
+                .execute(&mut self.ecs, &mut self.resources),
+            TurnState::PlayerTurn => self
+                .player_systems
+                .execute(&mut self.ecs, &mut self.resources),
+            TurnState::EnemyTurn => self
+                .enemy_systems
+                .execute(&mut self.ecs, &mut self.resources),
+            TurnState::NextLevel => self.change_level(true),
+            TurnState::PreviousLevel => self.change_level(false),
+            TurnState::SaveGame => {
+                let message = match self.save_game() {
+                    Ok(()) => format!("Game saved to {}", SAVE_FILE),
+                    Err(e) => format!("Save failed: {}", e),
+                };
+                self.log(message);
+            }
+            TurnState::LoadGame => {
+                // go back to the current run if the load fails
+                self.resources.insert(TurnState::AwaitingInput);
+                let message = match self.load_game() {
+                    Ok(()) => "Game loaded".to_string(),
+                    Err(e) => format!("Load failed: {}", e),
+                };
+                self.log(message);
+            }
+            // nothing to simulate, the caller decides whether to start again
+            TurnState::GameOver | TurnState::Victory => {}
+        }
+        if let Some(written) = self.record(before, action) {
+            let message = match written {
+                Ok(()) => format!("Replay saved to {}", REPLAY_FILE),
+                Err(e) => format!("Replay not saved: {}", e),
+            };
+            self.log(message);
+        }
+    }
+
+    // the result of writing the replay out, when the run has just ended
+    fn record(
+        &mut self,
+        before: TurnState,
+        action: Option<Action>,
+    ) -> Option<Result<(), SaveError>> {
+        let after = self.turn_state();
+        if let Some(recording) = &mut self.recording {
+            // only actions that used up the player's turn change the run
+            if let (TurnState::AwaitingInput, TurnState::PlayerTurn, Some(action)) =
+                (before, after, action)
+            {
+                recording.actions.push(action);
+            }
+
+            let run_ended = matches!(after, TurnState::GameOver | TurnState::Victory);
+            if run_ended && before != after {
+                return Some(recording.write(REPLAY_FILE));
+            }
+        }
+        None
+    }
+
+    pub fn save_game(&mut self) -> Result<(), SaveError> {
+        // a loaded game resumes waiting for input
+        self.resources.insert(TurnState::AwaitingInput);
+        SaveGame::capture(&self.ecs, &self.resources)?.write(SAVE_FILE)
+    }
+
+    // shown by the hud and the end screens
+    pub fn log(&mut self, message: impl Into<String>) {
+        self.resources.get_mut::<MessageLog>().unwrap().add(message);
+    }
+
+    // restore leaves the current game untouched if anything is wrong with the file
+    pub fn load_game(&mut self) -> Result<(), SaveError> {
+        SaveGame::read(SAVE_FILE)?.restore(&mut self.ecs, &mut self.resources)?;
+        // the actions so far no longer lead to this state
+        self.recording = None;
+        Ok(())
+    }
+
+    // leaves the current floor in the dungeon store and moves one floor up or down
+    fn change_level(&mut self, going_down: bool) {
+        // need to know which items are carried to keep them
+        let player_entity = *<Entity>::query()
+            .filter(component::<Player>())
+            .iter(&self.ecs)
+            .next()
+            .unwrap();
+
+        let mut entities_to_keep = HashSet::new();
+        entities_to_keep.insert(player_entity);
+
+        <(Entity, &Carried)>::query()
+            .iter(&self.ecs)
+            .filter(|(_, carried)| carried.0 == player_entity)
+            .map(|(entity, _)| *entity)
+            .for_each(|e| {
+                entities_to_keep.insert(e);
+            });
+
+        // monsters and items left behind are stored with the floor they were on
+        let left_behind: Vec<SavedEntity> = <(Entity, &Render)>::query()
+            .iter(&self.ecs)
+            .filter(|(entity, _)| !entities_to_keep.contains(*entity))
+            .filter_map(|(entity, render)| {
+                self.ecs
+                    .entry_ref(*entity)
+                    .ok()
+                    .map(|entry| snapshot_entity(&entry, *render))
+            })
+            .collect();
+
+        // remove all entities not in the keep list
+        // command buffer is fast and ensures no borrow conflicts
+        let mut cb = CommandBuffer::new(&self.ecs);
+        for e in Entity::query().iter(&self.ecs) {
+            if !entities_to_keep.contains(e) {
+                cb.remove(*e);
+            }
+        }
+
+        cb.flush(&mut self.ecs);
+
+        // set fov to dirty so it gets recalculated
+        <&mut FieldOfView>::query()
+            .iter_mut(&mut self.ecs)
+            .for_each(|fov| fov.is_dirty = true);
+
+        // the stairs ended the round early, a new floor starts with the player ready
+        <&mut Energy>::query()
+            .iter_mut(&mut self.ecs)
+            .for_each(|energy| energy.current = energy.current.max(ACTION_COST));
+
+        let old_level = <&Player>::query()
+            .iter(&self.ecs)
+            .map(|player| player.map_level)
+            .next()
+            .unwrap();
+        let map_level = if going_down {
+            old_level + 1
+        } else {
+            old_level - 1
+        };
+
+        let old_map = self.resources.remove::<Map>().unwrap();
+        let old_theme = self.resources.remove::<Box<dyn MapTheme>>().unwrap();
+        let mut levels = self.resources.remove::<DungeonLevels>().unwrap_or_default();
+        levels.store(
+            old_level,
+            StoredLevel {
+                map: old_map,
+                theme: old_theme.name().to_string(),
+                entities: left_behind,
+            },
+        );
+
+        let (map, theme) = match levels.take(map_level) {
+            Some(stored) => self.restore_level(stored, player_entity),
+            None => {
+                let map_builder = self.build_level(map_level);
+                (map_builder.map, map_builder.theme)
+            }
+        };
+
+        // arrive on the stairs that lead back to the floor we came from
+        let arrival_tile = if going_down {
+            TileType::UpStairs
+        } else {
+            TileType::Exit
+        };
+        let arrival = map
+            .tiles
+            .iter()
+            .position(|t| *t == arrival_tile)
+            .map(|idx| map.index_to_point2d(idx))
+            .expect("floor has no stairs to arrive on");
+
+        <(&mut Player, &mut Point)>::query()
+            .iter_mut(&mut self.ecs)
+            .for_each(|(player, pos)| {
+                player.map_level = map_level;
+                *pos = arrival;
+            });
+
+        self.resources.insert(Camera::new(arrival, &map));
+        self.resources.insert(map);
+        self.resources.insert(theme);
+        self.resources.insert(levels);
+        self.resources.insert(TurnState::AwaitingInput);
+    }
+
+    // brings a floor the player has been on before back exactly as it was left
+    fn restore_level(&mut self, stored: StoredLevel, player: Entity) -> (Map, Box<dyn MapTheme>) {
+        for saved in stored.entities.iter() {
+            let entity = self.ecs.push((saved.render,));
+            restore_entity(&mut self.ecs, entity, saved, player);
+        }
+        // stored themes always come from theme_by_name so the lookup can't fail
+        let theme = theme_by_name(&stored.theme).unwrap();
+        (stored.map, theme)
+    }
+
+    // generates a floor the player hasn't visited yet, seeded for this level
+    fn build_level(&mut self, map_level: u32) -> MapBuilder {
+        let mut rng = self.seed().level_rng(map_level);
+        let mut map_builder =
+            MapBuilder::for_floor(&mut rng, &self.campaign.floor(map_level), &self.prefabs);
+        map_builder.map.diagonal = self.campaign.diagonal_movement;
+        match &map_builder.build_error {
+            Some(e @ MapError::BadPrefab(_)) => self.log(e.to_string()),
+            Some(e) => self.log(format!("{}, using an empty map", e)),
+            None => {}
+        }
+
+        // the grail floor is the bottom of the run, every other floor has an exit down
+        if self.campaign.is_grail_floor(map_level) {
+            spawn_grail(&mut self.ecs, map_builder.grail_start);
+        } else {
+            let exit_idx = map_builder.map.point2d_to_index(map_builder.grail_start);
+            map_builder.map.tiles[exit_idx] = TileType::Exit;
+        }
+        // the player arrives at the start, stairs there lead back up
+        if map_level > 0 {
+            let stairs_idx = map_builder.map.point2d_to_index(map_builder.player_start);
+            map_builder.map.tiles[stairs_idx] = TileType::UpStairs;
+        }
+
+        spawn_level(
+            &mut self.ecs,
+            &mut rng,
+            map_level as usize,
+            &map_builder.monster_spawns,
+        );
+        spawn_named_entities(&mut self.ecs, &map_builder.entity_spawns);
+        map_builder
+    }
+}
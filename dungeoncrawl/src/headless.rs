@@ -0,0 +1,145 @@
+use crate::prelude::*;
+use std::collections::VecDeque;
+
+// supplies the player's actions when there is no keyboard
+pub trait ActionSource {
+    // None means the source has nothing left to do
+    fn next_action(&mut self, game: &Game) -> Option<Action>;
+}
+
+// plays back a fixed list of actions
+pub struct ScriptedActions {
+    actions: VecDeque<Action>,
+}
+
+impl ScriptedActions {
+    pub fn new(actions: Vec<Action>) -> Self {
+        Self {
+            actions: actions.into(),
+        }
+    }
+}
+
+impl ActionSource for ScriptedActions {
+    fn next_action(&mut self, _game: &Game) -> Option<Action> {
+        self.actions.pop_front()
+    }
+}
+
+// wanders around picking things up and using them, good for smoke testing
+pub struct RandomActions {
+    rng: RandomNumberGenerator,
+}
+
+impl RandomActions {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: RandomNumberGenerator::seeded(seed),
+        }
+    }
+}
+
+impl ActionSource for RandomActions {
+    fn next_action(&mut self, _game: &Game) -> Option<Action> {
+        // mostly move, never save or load
+        Some(match self.rng.range(0, 10) {
+            0 | 1 => Action::Move(Point::new(-1, 0)),
+            2 | 3 => Action::Move(Point::new(1, 0)),
+            4 | 5 => Action::Move(Point::new(0, -1)),
+            6 | 7 => Action::Move(Point::new(0, 1)),
+            8 => Action::Pickup,
+            _ => Action::Use(self.rng.range(0, 3) as usize),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    Victory,
+    Died,
+    OutOfActions,
+    TurnLimit,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SimulationReport {
+    pub seed: GameSeed,
+    pub outcome: Outcome,
+    pub turns: usize, // actions offered to the game, accepted or not
+    pub map_level: u32,
+}
+
+// plays one game to the end with no window, the same schedules run as in the real game
+pub fn simulate(
+    seed: GameSeed,
+    campaign: &Campaign,
+    source: &mut dyn ActionSource,
+    max_turns: usize,
+) -> SimulationReport {
+    let mut game = Game::with_campaign(Some(seed), campaign.clone());
+    // batches of simulations shouldn't keep overwriting the last replay
+    game.recording = None;
+    let mut turns = 0;
+
+    let outcome = loop {
+        match game.turn_state() {
+            TurnState::GameOver => break Outcome::Died,
+            TurnState::Victory => break Outcome::Victory,
+            TurnState::AwaitingInput => {
+                if turns >= max_turns {
+                    break Outcome::TurnLimit;
+                }
+                match source.next_action(&game) {
+                    Some(action) => {
+                        turns += 1;
+                        game.tick(Some(action));
+                    }
+                    None => break Outcome::OutOfActions,
+                }
+            }
+            _ => game.tick(None),
+        }
+    };
+
+    let map_level = <&Player>::query()
+        .iter(&game.ecs)
+        .map(|player| player.map_level)
+        .next()
+        .unwrap_or(0);
+
+    SimulationReport {
+        seed,
+        outcome,
+        turns,
+        map_level,
+    }
+}
+
+// runs a batch of random games from the command line and prints a summary
+pub fn run_simulations(games: usize, first_seed: u64, max_turns: usize, campaign: &Campaign) {
+    let mut victories = 0;
+    let mut deaths = 0;
+    for i in 0..games {
+        let seed = GameSeed(first_seed.wrapping_add(i as u64));
+        let report = simulate(seed, campaign, &mut RandomActions::new(seed.0), max_turns);
+        println!(
+            "seed {}: {:?} after {} turns on level {}",
+            report.seed.0,
+            report.outcome,
+            report.turns,
+            report.map_level + 1
+        );
+        match report.outcome {
+            Outcome::Victory => victories += 1,
+            Outcome::Died => deaths += 1,
+            _ => {}
+        }
+    }
+    println!(
+        "{} games: {} victories, {} deaths, {} unfinished",
+        games,
+        victories,
+        deaths,
+        games - victories - deaths
+    );
+}
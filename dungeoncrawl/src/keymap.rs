@@ -0,0 +1,107 @@
+use crate::prelude::*;
+use crate::spawner::open_resource;
+use ron::de::from_reader;
+use serde::Deserialize;
+
+// names the keymap file can use, the same as the VirtualKeyCode variants
+macro_rules! key_names {
+    ($($key:ident),* $(,)?) => {
+        &[$((stringify!($key), VirtualKeyCode::$key)),*]
+    };
+}
+
+const KEYS: &[(&str, VirtualKeyCode)] = key_names![
+    A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, Key0, Key1, Key2,
+    Key3, Key4, Key5, Key6, Key7, Key8, Key9, Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5,
+    Numpad6, Numpad7, Numpad8, Numpad9, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, Left,
+    Right, Up, Down, Home, End, PageUp, PageDown, Insert, Delete, Space, Return, Escape, Tab, Back,
+    Comma, Period, Slash, Semicolon, Minus, Equals,
+];
+
+pub fn key_by_name(name: &str) -> Option<VirtualKeyCode> {
+    KEYS.iter().find(|(n, _)| *n == name).map(|(_, key)| *key)
+}
+
+// how a key is shown on screen, digits without the Key in front
+fn key_label(name: &str) -> &str {
+    name.strip_prefix("Key").unwrap_or(name)
+}
+
+// which keys do what, loaded from resources/keymap.ron
+// an action can have any number of keys, a key only ever does one thing
+#[derive(Deserialize, Clone, Debug)]
+pub struct Keymap {
+    pub bindings: Vec<(Action, Vec<String>)>,
+}
+
+impl Keymap {
+    pub fn load() -> Self {
+        let file =
+            open_resource("resources/keymap.ron").expect("Failed to open resources/keymap.ron");
+        let keymap: Self = from_reader(file).expect("Failed to parse keymap.ron");
+        if let Err(e) = keymap.check() {
+            panic!("Invalid keymap.ron: {}", e);
+        }
+        keymap
+    }
+
+    // unknown names, keys bound twice and odd moves are caught when the file is loaded
+    pub fn check(&self) -> Result<(), String> {
+        let mut seen: Vec<(&str, Action)> = Vec::new();
+        for (i, (action, keys)) in self.bindings.iter().enumerate() {
+            if self.bindings[..i].iter().any(|(a, _)| a == action) {
+                return Err(format!("{} is listed twice", action.describe()));
+            }
+            let not_a_step = matches!(action, Action::Move(delta)
+                if delta.x.abs() > 1 || delta.y.abs() > 1 || *delta == Point::zero());
+            if not_a_step {
+                return Err(format!("{:?} is not one step", action));
+            }
+            for key in keys {
+                if key_by_name(key).is_none() {
+                    return Err(format!("unknown key {} for {}", key, action.describe()));
+                }
+                if let Some((_, other)) = seen.iter().find(|(k, _)| k == key) {
+                    return Err(format!(
+                        "{} is bound to both {} and {}",
+                        key,
+                        other.describe(),
+                        action.describe()
+                    ));
+                }
+                seen.push((key.as_str(), *action));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn action(&self, key: VirtualKeyCode) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.iter().any(|k| key_by_name(k) == Some(key)))
+            .map(|(action, _)| *action)
+    }
+
+    // the keys for an action ready to show the player, e.g. "F1 or Slash"
+    pub fn keys_for(&self, action: Action) -> String {
+        let keys: Vec<&str> = self
+            .bindings
+            .iter()
+            .filter(|(a, _)| *a == action)
+            .flat_map(|(_, keys)| keys.iter().map(|k| key_label(k)))
+            .collect();
+        if keys.is_empty() {
+            "(unbound)".to_string()
+        } else {
+            keys.join(" or ")
+        }
+    }
+
+    // one line per action for the help screen, in the order of the file
+    pub fn help_lines(&self) -> Vec<String> {
+        self.bindings
+            .iter()
+            .map(|(action, _)| format!("{:<24}{}", action.describe(), self.keys_for(*action)))
+            .collect()
+    }
+}
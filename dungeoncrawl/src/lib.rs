@@ -0,0 +1,61 @@
+// game logic for the dungeon crawler, main.rs only opens the window
+// split out so the rules can be driven headless and from integration tests
+
+pub mod action;
+pub mod behaviour;
+pub mod camera;
+pub mod campaign;
+pub mod components;
+pub mod dungeon;
+pub mod energy;
+pub mod flow_fields;
+pub mod game;
+pub mod headless;
+pub mod keymap;
+pub mod map;
+pub mod map_builder;
+pub mod message_log;
+pub mod occupancy;
+pub mod replay;
+pub mod savegame;
+pub mod seed;
+pub mod spawner;
+pub mod state;
+pub mod systems;
+pub mod turn_state;
+
+pub mod prelude {
+    pub use bracket_lib::prelude::*;
+    pub use legion::systems::CommandBuffer;
+    pub use legion::world::SubWorld;
+    pub use legion::*;
+    pub const SCREEN_WIDTH: i32 = 80;
+    pub const SCREEN_HEIGHT: i32 = 50;
+    pub const DISPLAY_WIDTH: i32 = SCREEN_WIDTH / 2;
+    pub const DISPLAY_HEIGHT: i32 = SCREEN_HEIGHT / 2;
+    // default dungeon size, maps are free to be smaller or larger than the screen
+    pub const MAP_WIDTH: i32 = 80;
+    pub const MAP_HEIGHT: i32 = 50;
+    pub use crate::action::*;
+    pub use crate::behaviour::*;
+    pub use crate::camera::*;
+    pub use crate::campaign::*;
+    pub use crate::components::*;
+    pub use crate::dungeon::*;
+    pub use crate::energy::*;
+    pub use crate::flow_fields::*;
+    pub use crate::game::*;
+    pub use crate::headless::*;
+    pub use crate::keymap::*;
+    pub use crate::map::*;
+    pub use crate::map_builder::*;
+    pub use crate::message_log::*;
+    pub use crate::occupancy::*;
+    pub use crate::replay::*;
+    pub use crate::savegame::*;
+    pub use crate::seed::*;
+    pub use crate::spawner::*;
+    pub use crate::state::*;
+    pub use crate::systems::*;
+    pub use crate::turn_state::*;
+}
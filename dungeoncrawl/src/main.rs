@@ -1,238 +1,9 @@
 // v1.0.0 - Initial release of dungeon crawler from hands-on rust by herbert wolverson
 
-mod camera;
-mod components;
-mod map;
-mod map_builder;
-mod spawner;
-mod systems;
-mod turn_state;
-
-mod prelude {
-    pub use bracket_lib::prelude::*;
-    pub use legion::systems::CommandBuffer;
-    pub use legion::world::SubWorld;
-    pub use legion::*;
-    pub const SCREEN_WIDTH: i32 = 80;
-    pub const SCREEN_HEIGHT: i32 = 50;
-    pub const DISPLAY_WIDTH: i32 = SCREEN_WIDTH / 2;
-    pub const DISPLAY_HEIGHT: i32 = SCREEN_HEIGHT / 2;
-    pub use crate::camera::*;
-    pub use crate::components::*;
-    pub use crate::map::*;
-    pub use crate::map_builder::*;
-    pub use crate::spawner::*;
-    pub use crate::systems::*;
-    pub use crate::turn_state::*;
-}
-
-use prelude::*;
+use dungeoncrawl::prelude::*;
 use std::env;
 use std::path::Path;
 
-struct State {
-    // Game state fields go here
-    ecs: World,
-    resources: Resources,
-    input_systems: Schedule,
-    player_systems: Schedule,
-    enemy_systems: Schedule,
-}
-
-impl State {
-    fn new() -> Self {
-        let mut ecs = World::default();
-        let mut resources = Resources::default();
-        let mut rng = RandomNumberGenerator::new();
-        let mut map_builder = MapBuilder::new(&mut rng);
-        spawn_player(&mut ecs, map_builder.player_start);
-        let exit_idx = map_builder.map.point2d_to_index(map_builder.grail_start);
-        map_builder.map.tiles[exit_idx] = TileType::Exit;
-        // have to use ecs and not self.ecs because self is not available in fn new()
-        spawn_level(&mut ecs, &mut rng, 0, &map_builder.monster_spawns);
-        resources.insert(map_builder.map);
-        resources.insert(Camera::new(map_builder.player_start));
-        resources.insert(TurnState::AwaitingInput);
-        resources.insert(map_builder.theme);
-
-        Self {
-            ecs,
-            resources,
-            input_systems: build_input_scheduler(),
-            player_systems: build_player_scheduler(),
-            enemy_systems: build_enemy_scheduler(),
-        }
-    }
-
-    fn reset_game_state(&mut self) {
-        self.ecs = World::default();
-        self.resources = Resources::default();
-        let mut rng = RandomNumberGenerator::new();
-        let mut map_builder = MapBuilder::new(&mut rng);
-        spawn_player(&mut self.ecs, map_builder.player_start);
-        let exit_idx = map_builder.map.point2d_to_index(map_builder.grail_start);
-        map_builder.map.tiles[exit_idx] = TileType::Exit;
-        spawn_level(&mut self.ecs, &mut rng, 0, &map_builder.monster_spawns);
-        self.resources.insert(map_builder.map);
-        self.resources.insert(Camera::new(map_builder.player_start));
-        self.resources.insert(TurnState::AwaitingInput);
-        self.resources.insert(map_builder.theme);
-    }
-
-    fn game_over(&mut self, ctx: &mut BTerm) {
-        ctx.cls();
-        ctx.set_active_console(2); // use top layer for UI
-        ctx.print_color_centered(10, RED, BLACK, "Your journey has ended.");
-        ctx.print_color_centered(
-            12,
-            WHITE,
-            BLACK,
-            "Slain by an enemy, your adventure is over.",
-        );
-        ctx.print_color_centered(
-            14,
-            WHITE,
-            BLACK,
-            "The grail remains unclaimed, and your home town is lost.",
-        );
-        ctx.print_color_centered(16, WHITE, BLACK, "Don't worry, you can always try again.");
-        ctx.print_color_centered(18, GREEN, BLACK, "Press 1 to play again.");
-
-        if let Some(VirtualKeyCode::Key1) = ctx.key {
-            self.reset_game_state();
-        }
-    }
-
-    fn victory(&mut self, ctx: &mut BTerm) {
-        ctx.cls();
-        ctx.set_active_console(2); // use top layer for UI
-        ctx.print_color_centered(10, GOLD, BLACK, "You found the Holy Grail!");
-        ctx.print_color_centered(
-            12,
-            WHITE,
-            BLACK,
-            "With the grail in hand, you return to your home town.",
-        );
-        ctx.print_color_centered(
-            14,
-            WHITE,
-            BLACK,
-            "The townsfolk rejoice as you bring them salvation.",
-        );
-        ctx.print_color_centered(16, WHITE, BLACK, "Congratulations on your victory!");
-        ctx.print_color_centered(18, GREEN, BLACK, "Press 1 to play again.");
-
-        if let Some(VirtualKeyCode::Key1) = ctx.key {
-            self.reset_game_state();
-        }
-    }
-
-    fn advance_level(&mut self) {
-        // need to know which items are carried to keep them
-        let player_entity = *<Entity>::query()
-            .filter(component::<Player>())
-            .iter(&mut self.ecs)
-            .nth(0)
-            .unwrap();
-
-        use std::collections::HashSet;
-        let mut entities_to_keep = HashSet::new();
-        entities_to_keep.insert(player_entity);
-
-        <(Entity, &Carried)>::query()
-            .iter(&self.ecs)
-            .filter(|(_, carried)| carried.0 == player_entity)
-            .map(|(entity, _)| *entity)
-            .for_each(|e| {
-                entities_to_keep.insert(e);
-            });
-
-        // remove all entities not in the keep list
-        // command buffer is fast and ensures no borrow conflicts
-        let mut cb = CommandBuffer::new(&mut self.ecs);
-        for e in Entity::query().iter(&self.ecs) {
-            if !entities_to_keep.contains(e) {
-                cb.remove(*e);
-            }
-        }
-
-        cb.flush(&mut self.ecs);
-
-        // set fov to dirty so it gets recalculated
-        <&mut FieldOfView>::query()
-            .iter_mut(&mut self.ecs)
-            .for_each(|fov| fov.is_dirty = true);
-
-        // build new map for next level
-        let mut rng = RandomNumberGenerator::new();
-        let mut map_builder = MapBuilder::new(&mut rng);
-
-        let mut map_level = 0;
-        <(&mut Player, &mut Point)>::query()
-            .iter_mut(&mut self.ecs)
-            .for_each(|(player, pos)| {
-                player.map_level += 1;
-                map_level = player.map_level;
-                pos.x = map_builder.player_start.x;
-                pos.y = map_builder.player_start.y;
-            });
-
-        // spawn grail on last level, exit on other levels
-        if map_level == 2 {
-            spawn_grail(&mut self.ecs, map_builder.grail_start);
-        } else {
-            let exit_idx = map_builder.map.point2d_to_index(map_builder.grail_start);
-            map_builder.map.tiles[exit_idx] = TileType::Exit;
-        }
-
-        // update monster and other resources
-        // need to include current level
-        spawn_level(
-            &mut self.ecs,
-            &mut rng,
-            map_level as usize,
-            &map_builder.monster_spawns,
-        );
-        self.resources.insert(map_builder.map);
-        self.resources.insert(Camera::new(map_builder.player_start));
-        self.resources.insert(TurnState::AwaitingInput);
-        self.resources.insert(map_builder.theme);
-    }
-}
-
-impl GameState for State {
-    fn tick(&mut self, ctx: &mut BTerm) {
-        // multiple layers
-        ctx.set_active_console(0);
-        ctx.cls();
-        ctx.set_active_console(1);
-        ctx.cls();
-        ctx.set_active_console(2);
-        ctx.cls();
-        self.resources.insert(ctx.key); // insert current key press into resources
-        ctx.set_active_console(0); // get mouse pos coordinates from correct layer
-        self.resources.insert(Point::from_tuple(ctx.mouse_pos())); // tuple of x,y coordinates
-        let current_state = self.resources.get::<TurnState>().unwrap().clone(); // requests a resource, gets an Option that needs to be unwrapped, clone to use a copy
-        match current_state {
-            TurnState::AwaitingInput => self
-                .input_systems
-                .execute(&mut self.ecs, &mut self.resources),
-            TurnState::PlayerTurn => self
-                .player_systems
-                .execute(&mut self.ecs, &mut self.resources),
-            TurnState::EnemyTurn => self
-                .enemy_systems
-                .execute(&mut self.ecs, &mut self.resources),
-            TurnState::NextLevel => {
-                self.advance_level();
-            }
-            TurnState::GameOver => self.game_over(ctx),
-            TurnState::Victory => self.victory(ctx),
-        }
-        render_draw_buffer(ctx).expect("Render error");
-    }
-}
-
 fn resource_root() -> String {
     let mut dir = env::current_exe()
         .expect("current_exe")
@@ -261,7 +32,68 @@ fn resource_root() -> String {
         .into_owned()
 }
 
+// value following a `--name` option on the command line
+fn arg_value(name: &str) -> Option<String> {
+    let args: Vec<String> = env::args().collect();
+    args.iter().position(|arg| arg == name).map(|i| {
+        args.get(i + 1)
+            .unwrap_or_else(|| panic!("{} needs a value", name))
+            .clone()
+    })
+}
+
+fn number_arg(name: &str) -> Option<u64> {
+    arg_value(name).map(|value| {
+        value
+            .parse()
+            .unwrap_or_else(|_| panic!("{} must be a whole number", name))
+    })
+}
+
 fn main() -> BError {
+    let seed = number_arg("--seed").map(GameSeed);
+
+    // `--architect <name>` builds every floor with one architect, e.g. `--architect empty`
+    // `--diagonal` turns on eight way movement
+    let mut campaign = Campaign::load();
+    if env::args().any(|arg| arg == "--diagonal") {
+        campaign.diagonal_movement = true;
+    }
+    if let Some(architect) = arg_value("--architect") {
+        campaign.architect = Some(architect);
+        if let Err(e) = campaign.check() {
+            panic!("{}", e);
+        }
+    }
+
+    // `--simulate <games>` plays random games without opening a window
+    if let Some(games) = number_arg("--simulate") {
+        let first_seed = seed.unwrap_or_else(GameSeed::random).0;
+        let max_turns = number_arg("--max-turns").unwrap_or(5000);
+        run_simulations(games as usize, first_seed, max_turns as usize, &campaign);
+        return Ok(());
+    }
+
+    // `--replay <file>` watches a recorded run, add `--headless` to just report the outcome
+    let replay = arg_value("--replay").map(|path| {
+        Replay::read(&path).unwrap_or_else(|e| panic!("Can't read replay {}: {}", path, e))
+    });
+    if let Some(replay) = &replay
+        && env::args().any(|arg| arg == "--headless")
+    {
+        replay.apply_to(&mut campaign);
+        let report = simulate(
+            replay.seed,
+            &campaign,
+            &mut ScriptedActions::new(replay.actions.clone()),
+            usize::MAX,
+        );
+        println!("{:?}", report);
+        return Ok(());
+    }
+
+    // bad key bindings stop the game before the window opens
+    let keymap = Keymap::load();
     let res = resource_root();
 
     let context = BTermBuilder::new()
@@ -277,5 +109,9 @@ fn main() -> BError {
         .with_simple_console_no_bg(SCREEN_WIDTH * 2, SCREEN_HEIGHT * 2, "terminal8x8.png")
         .build()?;
 
-    main_loop(context, State::new())
+    let state = match replay {
+        Some(replay) => State::watch(replay, campaign, keymap),
+        None => State::new(seed, campaign, keymap),
+    };
+    main_loop(context, state)
 }
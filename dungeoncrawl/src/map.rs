@@ -1,45 +1,136 @@
 use crate::prelude::*;
-const NUM_TILES: usize = (SCREEN_WIDTH * SCREEN_HEIGHT) as usize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum TileType {
     Wall,
     Floor,
-    Exit,
+    Exit,         // stairs down
+    UpStairs,     // stairs back to the previous floor
+    Door,         // closed, walking into it opens it
+    OpenDoor,     // can be closed again from a neighbouring tile
+    LockedDoor,   // opened like a door, but only while carrying a key
+    ShallowWater, // wadeable, but slow going
+    DeepWater,    // only crossed by a bridge
+    Lava,         // walkable, burns whoever is standing in it
+    Rubble,       // walkable, hard going
+    Bridge,       // floor laid over water
 }
 
+impl TileType {
+    pub fn is_walkable(self) -> bool {
+        matches!(
+            self,
+            TileType::Floor
+                | TileType::Exit
+                | TileType::UpStairs
+                | TileType::OpenDoor
+                | TileType::ShallowWater
+                | TileType::Lava
+                | TileType::Rubble
+                | TileType::Bridge
+        )
+    }
+
+    pub fn is_opaque(self) -> bool {
+        matches!(self, TileType::Wall | TileType::Door | TileType::LockedDoor)
+    }
+
+    // what stepping onto the tile costs a path, hazards cost extra so monsters go around them
+    pub fn movement_cost(self) -> f32 {
+        match self {
+            TileType::ShallowWater => 2.0,
+            TileType::Rubble => 3.0,
+            TileType::Lava => 10.0,
+            _ => 1.0,
+        }
+    }
+
+    // taken by anything with health ending a round on the tile
+    pub fn hazard_damage(self) -> i32 {
+        match self {
+            TileType::Lava => 3,
+            _ => 0,
+        }
+    }
+}
+
+// serializable so a run can be saved to disk, see savegame.rs
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Map {
+    pub width: i32,
+    pub height: i32,
     pub tiles: Vec<TileType>,
     pub revealed_tiles: Vec<bool>,
-}
-
-pub fn map_idx(x: i32, y: i32) -> usize {
-    ((y * SCREEN_WIDTH) + x) as usize
+    // eight way movement, copied from the campaign when the floor is built
+    #[serde(default)]
+    pub diagonal: bool,
 }
 
 impl Map {
-    pub fn new() -> Self {
+    // each level carries its own size, independent of the console
+    pub fn new(width: i32, height: i32) -> Self {
+        let num_tiles = (width * height) as usize;
         Self {
-            tiles: vec![TileType::Floor; NUM_TILES],
-            revealed_tiles: vec![false; NUM_TILES],
+            width,
+            height,
+            tiles: vec![TileType::Floor; num_tiles],
+            revealed_tiles: vec![false; num_tiles],
+            diagonal: false,
         }
     }
 
+    pub fn idx(&self, x: i32, y: i32) -> usize {
+        ((y * self.width) + x) as usize
+    }
+
     pub fn in_bounds(&self, point: Point) -> bool {
-        point.x >= 0 && point.x < SCREEN_WIDTH && point.y >= 0 && point.y < SCREEN_HEIGHT
+        point.x >= 0 && point.x < self.width && point.y >= 0 && point.y < self.height
     }
 
     pub fn can_enter_tile(&self, point: Point) -> bool {
+        self.in_bounds(point) && self.tiles[self.idx(point.x, point.y)].is_walkable()
+    }
+
+    // one step apart, diagonals only when they are turned on and not cutting past a blocked tile
+    // used for movement and for who is close enough to attack
+    pub fn is_step(&self, from: Point, to: Point) -> bool {
+        let delta = to - from;
+        match (delta.x.abs(), delta.y.abs()) {
+            (0, 1) | (1, 0) => true,
+            (1, 1) => {
+                self.diagonal
+                    && self.can_enter_tile(Point::new(to.x, from.y))
+                    && self.can_enter_tile(Point::new(from.x, to.y))
+            }
+            _ => false,
+        }
+    }
+
+    // closed and locked doors, the ones that block movement and sight
+    pub fn is_closed_door(&self, point: Point) -> bool {
         self.in_bounds(point)
-            && (self.tiles[map_idx(point.x, point.y)] == TileType::Floor
-                || self.tiles[map_idx(point.x, point.y)] == TileType::Exit)
+            && matches!(
+                self.tiles[self.idx(point.x, point.y)],
+                TileType::Door | TileType::LockedDoor
+            )
+    }
+
+    // a copy with every door opened, for asking what the player could reach
+    pub fn with_doors_open(&self) -> Map {
+        let mut map = self.clone();
+        map.tiles
+            .iter_mut()
+            .filter(|t| matches!(t, TileType::Door | TileType::LockedDoor))
+            .for_each(|t| *t = TileType::OpenDoor);
+        map
     }
 
     pub fn try_idx(&self, point: Point) -> Option<usize> {
         if !self.in_bounds(point) {
             None
         } else {
-            Some(map_idx(point.x, point.y))
+            Some(self.idx(point.x, point.y))
         }
     }
 
@@ -48,7 +139,7 @@ impl Map {
         let destination = loc + delta;
         // Check if within map boundaries
         if self.in_bounds(destination) {
-            if self.can_enter_tile(destination) {
+            if self.can_enter_tile(destination) && self.is_step(loc, destination) {
                 let idx = self.point2d_to_index(destination);
                 Some(idx)
             } else {
@@ -62,7 +153,7 @@ impl Map {
 
 impl Algorithm2D for Map {
     fn dimensions(&self) -> Point {
-        Point::new(SCREEN_WIDTH, SCREEN_HEIGHT)
+        Point::new(self.width, self.height)
     }
 
     fn in_bounds(&self, pos: Point) -> bool {
@@ -75,22 +166,33 @@ impl BaseMap for Map {
         let mut exits = SmallVec::new();
         let location = self.index_to_point2d(idx);
 
-        // if valid exit, add to exits list with cost of 1.0
-        // use lower cost to make some directions more favorable
+        // if valid exit, add to exits list with the cost of stepping onto that tile
         if let Some(idx) = self.valid_exit(location, Point::new(-1, 0)) {
-            exits.push((idx, 1.0))
+            exits.push((idx, self.tiles[idx].movement_cost()))
         }
 
         if let Some(idx) = self.valid_exit(location, Point::new(1, 0)) {
-            exits.push((idx, 1.0))
+            exits.push((idx, self.tiles[idx].movement_cost()))
         }
 
         if let Some(idx) = self.valid_exit(location, Point::new(0, -1)) {
-            exits.push((idx, 1.0))
+            exits.push((idx, self.tiles[idx].movement_cost()))
         }
 
         if let Some(idx) = self.valid_exit(location, Point::new(0, 1)) {
-            exits.push((idx, 1.0))
+            exits.push((idx, self.tiles[idx].movement_cost()))
+        }
+
+        // diagonal steps cover more ground, so they cost more
+        if self.diagonal {
+            for delta in [(-1, -1), (1, -1), (-1, 1), (1, 1)] {
+                if let Some(idx) = self.valid_exit(location, Point::new(delta.0, delta.1)) {
+                    exits.push((
+                        idx,
+                        self.tiles[idx].movement_cost() * std::f32::consts::SQRT_2,
+                    ))
+                }
+            }
         }
         exits
     }
@@ -102,6 +204,6 @@ impl BaseMap for Map {
     }
 
     fn is_opaque(&self, idx: usize) -> bool {
-        self.tiles[idx as usize] != TileType::Floor
+        self.tiles[idx].is_opaque()
     }
 }
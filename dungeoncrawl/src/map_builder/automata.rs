@@ -4,15 +4,8 @@ use crate::prelude::*;
 pub struct CellularAutomataArchitect {}
 
 impl MapArchitect for CellularAutomataArchitect {
-    fn new(&mut self, rng: &mut RandomNumberGenerator) -> MapBuilder {
-        let mut mb = MapBuilder {
-            map: Map::new(),
-            rooms: Vec::new(),
-            monster_spawns: Vec::new(),
-            player_start: Point::zero(),
-            grail_start: Point::zero(),
-            theme: super::themes::DungeonTheme::new(),
-        };
+    fn new(&mut self, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> MapBuilder {
+        let mut mb = MapBuilder::blank(width, height);
         self.random_noise_map(rng, &mut mb.map);
         for _ in 0..10 {
             self.iteration(&mut mb.map);
@@ -20,7 +13,7 @@ impl MapArchitect for CellularAutomataArchitect {
         let start = self.find_start(&mb.map);
         mb.monster_spawns = mb.spawn_monsters(&start, rng);
         mb.player_start = start;
-        mb.grail_start = mb.find_most_distant();
+        mb.grail_start = mb.find_most_distant().unwrap_or(mb.player_start);
 
         mb
     }
@@ -44,7 +37,7 @@ impl CellularAutomataArchitect {
         for dy in -1..=1 {
             for dx in -1..=1 {
                 // dont count current tile, only neighbours; incl diagonals
-                if !(dx == 0 && dy == 0) && map.tiles[map_idx(x + dx, y + dy)] == TileType::Wall {
+                if !(dx == 0 && dy == 0) && map.tiles[map.idx(x + dx, y + dy)] == TileType::Wall {
                     count_neighbours += 1;
                 }
             }
@@ -56,9 +49,9 @@ impl CellularAutomataArchitect {
         // copy of map to count neighbours without affecting current iteration
         let mut new_tiles = map.tiles.clone();
         // ignore edges of map to avoid out of bounds error
-        for y in 1..SCREEN_HEIGHT - 1 {
-            for x in 1..SCREEN_WIDTH - 1 {
-                let idx = map_idx(x, y);
+        for y in 1..map.height - 1 {
+            for x in 1..map.width - 1 {
+                let idx = map.idx(x, y);
                 let neighbours = self.count_neighbours(x, y, map);
                 if neighbours > 4 || neighbours == 0 {
                     new_tiles[idx] = TileType::Wall;
@@ -71,7 +64,7 @@ impl CellularAutomataArchitect {
     }
 
     fn find_start(&self, map: &Map) -> Point {
-        let center = Point::new(SCREEN_WIDTH / 2, SCREEN_HEIGHT / 2);
+        let center = Point::new(map.width / 2, map.height / 2);
         let closest_point = map
             .tiles
             // enumerate to get index and tile type
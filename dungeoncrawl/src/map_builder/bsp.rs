@@ -0,0 +1,106 @@
+use super::MapArchitect;
+use crate::prelude::*;
+
+// leaves are never split smaller than this, so each still fits a room and its walls
+const MIN_LEAF: i32 = 8;
+const MIN_ROOM: i32 = 3;
+
+pub struct BspArchitect {}
+
+impl MapArchitect for BspArchitect {
+    fn new(&mut self, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> MapBuilder {
+        let mut mb = MapBuilder::blank(width, height);
+        mb.fill(TileType::Wall);
+        // keep the outer edge as wall
+        let root = Rect::with_size(1, 1, (width - 2).max(1), (height - 2).max(1));
+        self.partition(root, rng, &mut mb);
+        mb.player_start = mb.rooms[0].center();
+        mb.grail_start = mb.find_most_distant().unwrap_or(mb.player_start);
+        for room in mb.rooms.iter().skip(1) {
+            mb.monster_spawns.push(room.center());
+        }
+
+        mb
+    }
+}
+
+impl BspArchitect {
+    // splits the leaf in two along its longer side until it is too small, a room goes in each
+    // returns one room from inside the leaf so the caller can join it to its sibling
+    fn partition(&self, leaf: Rect, rng: &mut RandomNumberGenerator, mb: &mut MapBuilder) -> Rect {
+        let can_split_x = leaf.width() >= MIN_LEAF * 2;
+        let can_split_y = leaf.height() >= MIN_LEAF * 2;
+        let split_x = match (can_split_x, can_split_y) {
+            (false, false) => return self.place_room(leaf, rng, mb),
+            (true, false) => true,
+            (false, true) => false,
+            (true, true) => leaf.width() >= leaf.height(),
+        };
+
+        let (first, second) = if split_x {
+            let at = rng.range(MIN_LEAF, leaf.width() - MIN_LEAF + 1);
+            (
+                Rect::with_size(leaf.x1, leaf.y1, at, leaf.height()),
+                Rect::with_size(leaf.x1 + at, leaf.y1, leaf.width() - at, leaf.height()),
+            )
+        } else {
+            let at = rng.range(MIN_LEAF, leaf.height() - MIN_LEAF + 1);
+            (
+                Rect::with_size(leaf.x1, leaf.y1, leaf.width(), at),
+                Rect::with_size(leaf.x1, leaf.y1 + at, leaf.width(), leaf.height() - at),
+            )
+        };
+
+        let first_room = self.partition(first, rng, mb);
+        let second_room = self.partition(second, rng, mb);
+        // joining every pair of siblings connects the whole tree
+        self.connect(first_room.center(), second_room.center(), rng, mb);
+
+        if rng.range(0, 2) == 0 {
+            first_room
+        } else {
+            second_room
+        }
+    }
+
+    // a random room inside the leaf, one tile in from its edges so neighbours don't touch
+    fn place_room(&self, leaf: Rect, rng: &mut RandomNumberGenerator, mb: &mut MapBuilder) -> Rect {
+        let max_width = (leaf.width() - 2).max(1);
+        let max_height = (leaf.height() - 2).max(1);
+        let width = rng.range(MIN_ROOM.min(max_width), max_width + 1);
+        let height = rng.range(MIN_ROOM.min(max_height), max_height + 1);
+        let x = leaf.x1 + Self::offset(leaf.width() - width, rng);
+        let y = leaf.y1 + Self::offset(leaf.height() - height, rng);
+        let room = Rect::with_size(x, y, width, height);
+
+        room.for_each(|p| {
+            if let Some(idx) = mb.map.try_idx(p) {
+                mb.map.tiles[idx] = TileType::Floor;
+            }
+        });
+        mb.rooms.push(room);
+        room
+    }
+
+    // leaves a wall on both sides when there is space for one
+    fn offset(free: i32, rng: &mut RandomNumberGenerator) -> i32 {
+        if free >= 2 { rng.range(1, free) } else { 0 }
+    }
+
+    fn connect(
+        &self,
+        from: Point,
+        to: Point,
+        rng: &mut RandomNumberGenerator,
+        mb: &mut MapBuilder,
+    ) {
+        // same L shaped tunnels as build_corridors
+        if rng.range(0, 2) == 1 {
+            mb.apply_horizontal_tunnel(from.x, to.x, from.y);
+            mb.apply_vertical_tunnel(from.y, to.y, to.x);
+        } else {
+            mb.apply_vertical_tunnel(from.y, to.y, from.x);
+            mb.apply_horizontal_tunnel(from.x, to.x, to.y);
+        }
+    }
+}
@@ -3,21 +3,14 @@ use crate::prelude::*;
 pub struct DrunkardWalkArchitect {}
 
 const STAGGER_DISTANCE: usize = 400;
-const NUM_TILES: usize = (SCREEN_WIDTH * SCREEN_HEIGHT) as usize;
-const DESIRED_FLOOR_TILES: usize = NUM_TILES / 3;
 
 impl MapArchitect for DrunkardWalkArchitect {
-    fn new(&mut self, rng: &mut RandomNumberGenerator) -> MapBuilder {
-        let mut mb = MapBuilder {
-            map: Map::new(),
-            rooms: Vec::new(),
-            monster_spawns: Vec::new(),
-            player_start: Point::zero(),
-            grail_start: Point::zero(),
-            theme: super::themes::DungeonTheme::new(),
-        };
+    fn new(&mut self, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> MapBuilder {
+        let mut mb = MapBuilder::blank(width, height);
         mb.fill(TileType::Wall);
-        let center = Point::new(SCREEN_WIDTH / 2, SCREEN_HEIGHT / 2);
+        // carve out a third of the map, whatever its size
+        let desired_floor_tiles = mb.map.tiles.len() / 3;
+        let center = Point::new(width / 2, height / 2);
         self.drunkard(&center, rng, &mut mb.map);
         while mb
             .map
@@ -25,17 +18,17 @@ impl MapArchitect for DrunkardWalkArchitect {
             .iter()
             .filter(|t| **t == TileType::Floor)
             .count()
-            < DESIRED_FLOOR_TILES
+            < desired_floor_tiles
         {
             self.drunkard(
-                &Point::new(rng.range(0, SCREEN_WIDTH), rng.range(0, SCREEN_HEIGHT)),
+                &Point::new(rng.range(0, width), rng.range(0, height)),
                 rng,
                 &mut mb.map,
             );
             let dijkstra_map = DijkstraMap::new(
-                SCREEN_WIDTH,
-                SCREEN_HEIGHT,
-                &vec![mb.map.point2d_to_index(center)],
+                width,
+                height,
+                &[mb.map.point2d_to_index(center)],
                 &mb.map,
                 1024.0,
             );
@@ -48,7 +41,7 @@ impl MapArchitect for DrunkardWalkArchitect {
         }
         mb.monster_spawns = mb.spawn_monsters(&center, rng);
         mb.player_start = center;
-        mb.grail_start = mb.find_most_distant();
+        mb.grail_start = mb.find_most_distant().unwrap_or(mb.player_start);
 
         mb
     }
@@ -56,7 +49,7 @@ impl MapArchitect for DrunkardWalkArchitect {
 
 impl DrunkardWalkArchitect {
     fn drunkard(&mut self, start: &Point, rng: &mut RandomNumberGenerator, map: &mut Map) {
-        let mut drunkard_pos = start.clone();
+        let mut drunkard_pos = *start;
         let mut staggered_distance = 0;
 
         loop {
@@ -5,23 +5,14 @@ use crate::prelude::*;
 pub struct EmptyArchitect {}
 
 impl MapArchitect for EmptyArchitect {
-    fn new(&mut self, rng: &mut RandomNumberGenerator) -> MapBuilder {
-        let mut mb = MapBuilder {
-            map: Map::new(),
-            rooms: Vec::new(),
-            monster_spawns: Vec::new(),
-            player_start: Point::zero(),
-            grail_start: Point::zero(),
-            theme: super::themes::DungeonTheme::new(),
-        };
+    fn new(&mut self, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> MapBuilder {
+        let mut mb = MapBuilder::blank(width, height);
         mb.fill(TileType::Floor);
-        mb.player_start = Point::new(SCREEN_WIDTH / 2, SCREEN_HEIGHT / 2);
-        mb.grail_start = mb.find_most_distant();
+        mb.player_start = Point::new(width / 2, height / 2);
+        mb.grail_start = mb.find_most_distant().unwrap_or(mb.player_start);
         for _ in 0..50 {
-            mb.monster_spawns.push(Point::new(
-                rng.range(1, SCREEN_WIDTH),
-                rng.range(1, SCREEN_HEIGHT),
-            ));
+            mb.monster_spawns
+                .push(Point::new(rng.range(1, width), rng.range(1, height)));
         }
         mb
     }
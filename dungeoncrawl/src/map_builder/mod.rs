@@ -1,82 +1,246 @@
 mod automata;
+mod bsp;
 mod drunkard;
-// mod empty;
+mod empty;
+mod post_process;
 mod prefab;
+mod registry;
 mod rooms;
 mod themes;
+mod validation;
+mod voronoi;
+mod wfc;
 
 use crate::prelude::*;
-use automata::CellularAutomataArchitect;
-use drunkard::DrunkardWalkArchitect;
-// use empty::EmptyArchitect;
-use prefab::apply_prefab;
-use rooms::RoomsArchitect;
+use empty::EmptyArchitect;
+pub use post_process::{MapPostProcessor, PostStep, Symmetry, default_post_processing};
+pub use prefab::{LegendEntry, Prefab, PrefabKind, Prefabs};
+pub use registry::{ArchitectFactory, ArchitectRegistry};
+use std::sync::Arc;
 use themes::*;
+pub use themes::{THEME_NAMES, theme_by_name};
+pub use validation::MapError;
 
-trait MapArchitect {
-    fn new(&mut self, rng: &mut RandomNumberGenerator) -> MapBuilder;
+// implement this and register it with an ArchitectRegistry to add a generator
+pub trait MapArchitect {
+    // named new since the book started with it, even though it takes self and builds a MapBuilder
+    #[allow(clippy::new_ret_no_self, clippy::wrong_self_convention)]
+    fn new(&mut self, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> MapBuilder;
+
+    // told the floor's depth before new is called, for architects that care
+    fn set_level(&mut self, _level: u32) {}
+
+    // handed the prefabs loaded at startup, for architects that draw on them
+    fn set_prefabs(&mut self, _prefabs: &Arc<Prefabs>) {}
 }
 
 // only implemented by types that are sync + send
 pub trait MapTheme: Sync + Send {
+    // stable name for the theme, written to save files
+    fn name(&self) -> &'static str;
     fn tile_to_render(&self, tile_type: TileType) -> FontCharType;
 }
 
+// doors only count as walls once the game is running, validate makes sure there is a key
+// for every locked one
+fn is_open_tile(map: &Map, point: Point) -> bool {
+    map.try_idx(point).is_some_and(|idx| {
+        let tile = map.tiles[idx];
+        tile.is_walkable() || matches!(tile, TileType::Door | TileType::LockedDoor)
+    })
+}
+
+// distances from start with every door open, no depth limit so large maps aren't cut off
+fn distances_from(map: &Map, start: Point) -> DijkstraMap {
+    DijkstraMap::new(
+        map.width,
+        map.height,
+        &[map.point2d_to_index(start)],
+        &map.with_doors_open(),
+        f32::MAX,
+    )
+}
+
+// distances from start through plain doors only, what can be reached before finding a key
+fn distances_without_keys(map: &Map, start: Point) -> DijkstraMap {
+    let mut map = map.clone();
+    map.tiles
+        .iter_mut()
+        .filter(|t| **t == TileType::Door)
+        .for_each(|t| *t = TileType::OpenDoor);
+    DijkstraMap::new(
+        map.width,
+        map.height,
+        &[map.point2d_to_index(start)],
+        &map,
+        f32::MAX,
+    )
+}
+
+// walls up every floor tile the start can't reach, returns how many tiles are left reachable
+fn cull_unreachable(map: &mut Map, start: Point) -> usize {
+    let dijkstra_map = distances_from(map, start);
+    let mut reachable = 0;
+    for (idx, distance) in dijkstra_map.map.iter().enumerate() {
+        // unreachable tiles keep the starting value
+        if *distance < f32::MAX {
+            reachable += 1;
+        } else {
+            map.tiles[idx] = TileType::Wall;
+        }
+    }
+    reachable
+}
+
+// how many maps a floor gets through before falling back to an empty one
+const MAX_BUILD_ATTEMPTS: usize = 5;
+
 const NUM_ROOMS: usize = 20;
+// rooms are up to this size less one, smaller on maps that can't fit them
+const MAX_ROOM_SIZE: i32 = 10;
+// small maps can't fit NUM_ROOMS, stop trying eventually
+const MAX_ROOM_ATTEMPTS: usize = 1000;
 
 pub struct MapBuilder {
     pub map: Map,
     pub rooms: Vec<Rect>,
     pub monster_spawns: Vec<Point>,
+    pub entity_spawns: Vec<(Point, String)>, // named templates placed by prefabs
     pub player_start: Point,
     pub grail_start: Point,
     pub theme: Box<dyn MapTheme>,
+    pub level: u32,
+    pub build_error: Option<MapError>, // why the floor fell back to an empty map or lost a prefab
 }
 
 impl MapBuilder {
-    pub fn new(rng: &mut RandomNumberGenerator) -> Self {
-        let mut architect: Box<dyn MapArchitect> = match rng.range(0, 3) {
-            0 => Box::new(CellularAutomataArchitect {}),
-            1 => Box::new(DrunkardWalkArchitect {}),
-            _ => Box::new(RoomsArchitect {}),
-        };
-        let mut mb = architect.new(rng);
-        apply_prefab(&mut mb, rng);
+    pub fn new(
+        rng: &mut RandomNumberGenerator,
+        width: i32,
+        height: i32,
+        prefabs: &Arc<Prefabs>,
+    ) -> Self {
+        Self::for_floor(rng, &Floor::any(width, height), prefabs)
+    }
+
+    pub fn for_floor(
+        rng: &mut RandomNumberGenerator,
+        floor: &Floor,
+        prefabs: &Arc<Prefabs>,
+    ) -> Self {
+        Self::with_registry(rng, floor, &ArchitectRegistry::standard(), prefabs)
+    }
+
+    // names in the floor are checked when the campaign is loaded
+    // a floor that never builds a valid map gets an empty one rather than stopping the game
+    // with the reason kept in build_error for the caller to report
+    pub fn with_registry(
+        rng: &mut RandomNumberGenerator,
+        floor: &Floor,
+        registry: &ArchitectRegistry,
+        prefabs: &Arc<Prefabs>,
+    ) -> Self {
+        match Self::try_build(rng, floor, registry, prefabs) {
+            Ok(mb) => mb,
+            Err(e) => {
+                let mut mb = EmptyArchitect {}.new(rng, floor.width, floor.height);
+                mb.level = floor.level;
+                mb.theme = Self::choose_theme(rng, floor);
+                mb.build_error = Some(e);
+                mb
+            }
+        }
+    }
+
+    // builds, validates and repairs, starting over with a new map when repair isn't enough
+    pub fn try_build(
+        rng: &mut RandomNumberGenerator,
+        floor: &Floor,
+        registry: &ArchitectRegistry,
+        prefabs: &Arc<Prefabs>,
+    ) -> Result<Self, MapError> {
+        let mut last = None;
+        for _ in 0..MAX_BUILD_ATTEMPTS {
+            let mut architect = registry
+                .choose(rng, &floor.architects)
+                .expect("floor has no architect to build with");
+            architect.set_level(floor.level);
+            architect.set_prefabs(prefabs);
+            let mut mb = architect.new(rng, floor.width, floor.height);
+            mb.level = floor.level;
+            mb.post_process(&floor.post_processing, prefabs, rng);
+            mb.apply_density(floor.monster_density, rng);
+
+            match mb.validate().or_else(|_| mb.repair()) {
+                Ok(()) => {
+                    mb.theme = Self::choose_theme(rng, floor);
+                    return Ok(mb);
+                }
+                Err(e) => last = Some(e),
+            }
+        }
+        Err(MapError::GaveUp {
+            attempts: MAX_BUILD_ATTEMPTS,
+            last: Box::new(last.expect("at least one attempt is made")),
+        })
+    }
+
+    fn choose_theme(rng: &mut RandomNumberGenerator, floor: &Floor) -> Box<dyn MapTheme> {
+        let theme_name = &floor.themes[rng.range(0, floor.themes.len())];
+        theme_by_name(theme_name).expect("unknown theme in campaign")
+    }
+
+    // runs each step in order on the architect's map
+    pub fn post_process(
+        &mut self,
+        steps: &[PostStep],
+        prefabs: &Arc<Prefabs>,
+        rng: &mut RandomNumberGenerator,
+    ) {
+        for step in steps {
+            step.processor(prefabs).apply(self, rng);
+        }
+    }
 
-        mb.theme = match rng.range(0, 2) {
-            0 => DungeonTheme::new(),
-            _ => ForestTheme::new(),
-        };
+    // starting point for every architect, an all floor map of the given size
+    pub fn blank(width: i32, height: i32) -> Self {
+        Self {
+            map: Map::new(width, height),
+            rooms: Vec::new(),
+            monster_spawns: Vec::new(),
+            entity_spawns: Vec::new(),
+            player_start: Point::zero(),
+            grail_start: Point::zero(),
+            theme: DungeonTheme::new(),
+            level: 0,
+            build_error: None,
+        }
+    }
 
-        mb
+    // the floor tile closest to the middle of the map, None if there is no floor at all
+    pub fn central_floor(map: &Map) -> Option<Point> {
+        post_process::nearest_floor(map, Point::new(map.width / 2, map.height / 2))
     }
 
-    fn fill(&mut self, tile: TileType) {
+    pub fn fill(&mut self, tile: TileType) {
         self.map.tiles.iter_mut().for_each(|t| *t = tile);
     }
 
-    fn find_most_distant(&self) -> Point {
-        let dijkstra_map = DijkstraMap::new(
-            SCREEN_WIDTH,
-            SCREEN_HEIGHT,
-            &vec![self.map.point2d_to_index(self.player_start)],
-            &self.map,
-            1024.0,
-        );
+    // furthest reachable tile from player_start, None if the start is off the map
+    pub fn find_most_distant(&self) -> Option<Point> {
+        self.map.try_idx(self.player_start)?;
+        let dijkstra_map = distances_from(&self.map, self.player_start);
 
         // find furthest point from player start
         const UNREACHABLE: &f32 = &f32::MAX;
-        self.map.index_to_point2d(
-            dijkstra_map
-                .map
-                .iter()
-                .enumerate()
-                .filter(|(_, distance)| *distance < UNREACHABLE) // filter out unreachable tiles
-                // cant use max since entry is borrowed, so use max_by with partial_cmp
-                .max_by(|a, b| a.1.partial_cmp(b.1).unwrap()) // get furthest reachable tile
-                .unwrap()
-                .0, // get first item of tuple, the index
-        )
+        dijkstra_map
+            .map
+            .iter()
+            .enumerate()
+            .filter(|(_, distance)| *distance < UNREACHABLE) // filter out unreachable tiles
+            // cant use max since entry is borrowed, so use max_by with partial_cmp
+            .max_by(|a, b| a.1.partial_cmp(b.1).unwrap()) // get furthest reachable tile
+            .map(|(idx, _)| self.map.index_to_point2d(idx))
     }
 
     fn apply_horizontal_tunnel(&mut self, x1: i32, x2: i32, y: i32) {
@@ -85,8 +249,7 @@ impl MapBuilder {
         for x in min(x1, x2)..=max(x1, x2) {
             // check if point is in bounds, then set to floor
             if let Some(idx) = self.map.try_idx(Point::new(x, y)) {
-                // try_idx returns an Option for convenience, have to convert to usize
-                self.map.tiles[idx as usize] = TileType::Floor;
+                self.map.tiles[idx] = TileType::Floor;
             }
         }
     }
@@ -95,7 +258,7 @@ impl MapBuilder {
         use std::cmp::{max, min};
         for y in min(y1, y2)..=max(y1, y2) {
             if let Some(idx) = self.map.try_idx(Point::new(x, y)) {
-                self.map.tiles[idx as usize] = TileType::Floor;
+                self.map.tiles[idx] = TileType::Floor;
             }
         }
     }
@@ -103,7 +266,7 @@ impl MapBuilder {
     fn build_corridors(&mut self, rng: &mut RandomNumberGenerator) {
         let mut rooms = self.rooms.clone();
         // get sorted rooms (by center point) to reduce long corridors
-        rooms.sort_by(|a, b| a.center().x.cmp(&b.center().x));
+        rooms.sort_by_key(|room| room.center().x);
 
         // skip first entry so we can reference previous room
         for (i, room) in rooms.iter().enumerate().skip(1) {
@@ -123,12 +286,18 @@ impl MapBuilder {
 
     fn build_random_rooms(&mut self, rng: &mut RandomNumberGenerator) {
         // keep generating rooms until we have enough
-        while self.rooms.len() < NUM_ROOMS {
+        let mut attempts = 0;
+        // the same rolls as always on maps big enough, so seeds keep their maps
+        let (width, height) = (self.map.width, self.map.height);
+        let max_width = MAX_ROOM_SIZE.min(width - 1).max(3);
+        let max_height = MAX_ROOM_SIZE.min(height - 1).max(3);
+        while self.rooms.len() < NUM_ROOMS && attempts < MAX_ROOM_ATTEMPTS {
+            attempts += 1;
             let room = Rect::with_size(
-                rng.range(1, SCREEN_WIDTH - 10),
-                rng.range(1, SCREEN_HEIGHT - 10),
-                rng.range(2, 10),
-                rng.range(2, 10),
+                rng.range(1, (width - MAX_ROOM_SIZE).max(2)),
+                rng.range(1, (height - MAX_ROOM_SIZE).max(2)),
+                rng.range(2, max_width),
+                rng.range(2, max_height),
             );
             let mut overlap = false;
             for r in self.rooms.iter() {
@@ -139,8 +308,8 @@ impl MapBuilder {
             if !overlap {
                 // if no overlap, check they are within boundaries, set contents to floor
                 room.for_each(|p| {
-                    if p.x > 0 && p.x < SCREEN_WIDTH && p.y > 0 && p.y < SCREEN_HEIGHT {
-                        let idx = map_idx(p.x, p.y);
+                    if p.x > 0 && p.x < self.map.width && p.y > 0 && p.y < self.map.height {
+                        let idx = self.map.idx(p.x, p.y);
                         self.map.tiles[idx] = TileType::Floor;
                     }
                 });
@@ -149,6 +318,37 @@ impl MapBuilder {
         }
     }
 
+    // thin out or add to the architect's spawn points
+    fn apply_density(&mut self, density: f32, rng: &mut RandomNumberGenerator) {
+        let wanted = (self.monster_spawns.len() as f32 * density).round() as usize;
+        while self.monster_spawns.len() > wanted {
+            let idx = rng.random_slice_index(&self.monster_spawns).unwrap();
+            self.monster_spawns.remove(idx);
+        }
+        if self.monster_spawns.len() < wanted {
+            // same rules as spawn_monsters, away from the player and not doubled up
+            let mut free: Vec<Point> = self
+                .map
+                .tiles
+                .iter()
+                .enumerate()
+                .filter(|(_, tile)| **tile == TileType::Floor)
+                .map(|(idx, _)| self.map.index_to_point2d(idx))
+                .filter(|p| {
+                    DistanceAlg::Pythagoras.distance2d(self.player_start, *p) > 10.0
+                        && *p != self.grail_start
+                        && !self.monster_spawns.contains(p)
+                })
+                .collect();
+            while self.monster_spawns.len() < wanted {
+                let Some(idx) = rng.random_slice_index(&free) else {
+                    break;
+                };
+                self.monster_spawns.push(free.remove(idx));
+            }
+        }
+    }
+
     fn spawn_monsters(&self, start: &Point, rng: &mut RandomNumberGenerator) -> Vec<Point> {
         const NUM_MONSTERS: usize = 50;
         let mut spawnable_tiles: Vec<Point> = self
@@ -168,8 +368,11 @@ impl MapBuilder {
         let mut spawns = Vec::new();
         for _ in 0..NUM_MONSTERS {
             // randomly select a tile from slice of available spawnable tiles
-            let target_index = rng.random_slice_index(&spawnable_tiles).unwrap();
-            spawns.push(spawnable_tiles[target_index].clone());
+            // small maps can run out of tiles before all monsters are placed
+            let Some(target_index) = rng.random_slice_index(&spawnable_tiles) else {
+                break;
+            };
+            spawns.push(spawnable_tiles[target_index]);
             // remove from available tiles so we dont spawn multiple monsters in same spot
             spawnable_tiles.remove(target_index);
         }
@@ -0,0 +1,378 @@
+use super::prefab::{PrefabKind, Prefabs, apply_prefab, size_of};
+use super::{cull_unreachable, distances_from};
+use crate::prelude::*;
+use serde::Deserialize;
+use std::sync::Arc;
+
+// a step run on a finished map, floors list them in campaign.ron to combine into new styles
+pub trait MapPostProcessor {
+    fn apply(&self, mb: &mut MapBuilder, rng: &mut RandomNumberGenerator);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub enum Symmetry {
+    Horizontal, // left half copied onto the right
+    Vertical,   // top half copied onto the bottom
+    Both,
+}
+
+// the steps a floor can name, run in the order given
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub enum PostStep {
+    CullUnreachable,
+    SmoothWalls,
+    AddDoors,
+    Prefab,
+    Vaults,
+    Mirror(Symmetry),
+    WidenCorridors,
+    RecomputeStarts,
+}
+
+impl PostStep {
+    // prefabs are only kept by the steps that stamp them
+    pub fn processor(&self, prefabs: &Arc<Prefabs>) -> Box<dyn MapPostProcessor> {
+        match self {
+            PostStep::CullUnreachable => Box::new(CullUnreachable {}),
+            PostStep::SmoothWalls => Box::new(SmoothWalls {}),
+            PostStep::AddDoors => Box::new(AddDoors {}),
+            PostStep::Prefab => Box::new(PlacePrefab {
+                prefabs: Arc::clone(prefabs),
+            }),
+            PostStep::Vaults => Box::new(PlaceVaults {
+                prefabs: Arc::clone(prefabs),
+            }),
+            PostStep::Mirror(symmetry) => Box::new(Mirror {
+                symmetry: *symmetry,
+            }),
+            PostStep::WidenCorridors => Box::new(WidenCorridors {}),
+            PostStep::RecomputeStarts => Box::new(RecomputeStarts {}),
+        }
+    }
+}
+
+// what every floor ran before the pipeline existed
+pub fn default_post_processing() -> Vec<PostStep> {
+    vec![PostStep::AddDoors, PostStep::Vaults, PostStep::Prefab]
+}
+
+fn is_wall(map: &Map, x: i32, y: i32) -> bool {
+    map.try_idx(Point::new(x, y))
+        .is_none_or(|idx| map.tiles[idx] == TileType::Wall)
+}
+
+fn on_edge(map: &Map, x: i32, y: i32) -> bool {
+    x == 0 || y == 0 || x == map.width - 1 || y == map.height - 1
+}
+
+pub(super) fn nearest_floor(map: &Map, target: Point) -> Option<Point> {
+    map.tiles
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| **t == TileType::Floor)
+        .map(|(idx, _)| map.index_to_point2d(idx))
+        .min_by(|a, b| {
+            DistanceAlg::Pythagoras
+                .distance2d(target, *a)
+                .partial_cmp(&DistanceAlg::Pythagoras.distance2d(target, *b))
+                .unwrap()
+        })
+}
+
+// an earlier step may have walled over the start, move it to the closest floor
+pub(super) fn keep_start_on_floor(mb: &mut MapBuilder) -> bool {
+    if mb.map.can_enter_tile(mb.player_start) {
+        return true;
+    }
+    match nearest_floor(&mb.map, mb.player_start) {
+        Some(p) => {
+            mb.player_start = p;
+            true
+        }
+        None => false,
+    }
+}
+
+pub struct CullUnreachable {}
+
+impl MapPostProcessor for CullUnreachable {
+    fn apply(&self, mb: &mut MapBuilder, _rng: &mut RandomNumberGenerator) {
+        if !keep_start_on_floor(mb) {
+            return;
+        }
+        cull_unreachable(&mut mb.map, mb.player_start);
+        let map = &mb.map;
+        mb.monster_spawns.retain(|p| map.can_enter_tile(*p));
+        mb.entity_spawns.retain(|(p, _)| map.can_enter_tile(*p));
+    }
+}
+
+// knocks out wall tiles that are mostly surrounded by floor
+pub struct SmoothWalls {}
+
+impl MapPostProcessor for SmoothWalls {
+    fn apply(&self, mb: &mut MapBuilder, _rng: &mut RandomNumberGenerator) {
+        let map = &mb.map;
+        let mut new_tiles = map.tiles.clone();
+        for y in 1..map.height - 1 {
+            for x in 1..map.width - 1 {
+                if !is_wall(map, x, y) {
+                    continue;
+                }
+                let mut walls = 0;
+                for dy in -1..=1 {
+                    for dx in -1..=1 {
+                        if !(dx == 0 && dy == 0) && is_wall(map, x + dx, y + dy) {
+                            walls += 1;
+                        }
+                    }
+                }
+                if walls <= 2 {
+                    new_tiles[map.idx(x, y)] = TileType::Floor;
+                }
+            }
+        }
+        mb.map.tiles = new_tiles;
+    }
+}
+
+// puts a door where a corridor meets a room, only room based maps have rooms
+pub struct AddDoors {}
+
+impl MapPostProcessor for AddDoors {
+    fn apply(&self, mb: &mut MapBuilder, _rng: &mut RandomNumberGenerator) {
+        for room in mb.rooms.clone().iter() {
+            // the ring of tiles just outside the room
+            let mut ring = Vec::new();
+            for x in room.x1..room.x2 {
+                ring.push(Point::new(x, room.y1 - 1));
+                ring.push(Point::new(x, room.y2));
+            }
+            for y in room.y1..room.y2 {
+                ring.push(Point::new(room.x1 - 1, y));
+                ring.push(Point::new(room.x2, y));
+            }
+
+            for p in ring {
+                let Some(idx) = mb.map.try_idx(p) else {
+                    continue;
+                };
+                if mb.map.tiles[idx] != TileType::Floor
+                    || p == mb.player_start
+                    || p == mb.grail_start
+                    || mb.monster_spawns.contains(&p)
+                {
+                    continue;
+                }
+                // a one tile gap in a wall, running either way
+                let across = is_wall(&mb.map, p.x - 1, p.y) && is_wall(&mb.map, p.x + 1, p.y);
+                let along = is_wall(&mb.map, p.x, p.y - 1) && is_wall(&mb.map, p.x, p.y + 1);
+                let next_to_door = [(-1, 0), (1, 0), (0, -1), (0, 1)].iter().any(|(dx, dy)| {
+                    mb.map
+                        .try_idx(Point::new(p.x + dx, p.y + dy))
+                        .is_some_and(|i| mb.map.tiles[i] == TileType::Door)
+                });
+                if (across != along) && !next_to_door {
+                    mb.map.tiles[idx] = TileType::Door;
+                }
+            }
+        }
+    }
+}
+
+pub struct PlacePrefab {
+    prefabs: Arc<Prefabs>,
+}
+
+impl MapPostProcessor for PlacePrefab {
+    fn apply(&self, mb: &mut MapBuilder, rng: &mut RandomNumberGenerator) {
+        apply_prefab(&self.prefabs, mb, rng);
+    }
+}
+
+// one in VAULT_CHANCE rooms gets a vault that fits inside it
+const VAULT_CHANCE: i32 = 4;
+// floor left around a vault so corridors into the room can still get round it
+const VAULT_MARGIN: i32 = 2;
+
+pub struct PlaceVaults {
+    prefabs: Arc<Prefabs>,
+}
+
+impl MapPostProcessor for PlaceVaults {
+    fn apply(&self, mb: &mut MapBuilder, rng: &mut RandomNumberGenerator) {
+        let prefabs = &self.prefabs;
+        for room in mb.rooms.clone().iter() {
+            // the player and the way down stay clear
+            if room.point_in_rect(mb.player_start) || room.point_in_rect(mb.grail_start) {
+                continue;
+            }
+            if rng.range(0, VAULT_CHANCE) != 0 {
+                continue;
+            }
+            let (room_width, room_height) = (room.width(), room.height());
+            let Some(vault) = prefabs.choose(PrefabKind::Vault, mb.level, rng, |p| {
+                p.width().max(p.height()) + VAULT_MARGIN <= room_width.min(room_height)
+            }) else {
+                continue;
+            };
+            let rows = vault.oriented(rng);
+            let (width, height) = size_of(&rows);
+            let at = Point::new(
+                room.x1 + (room_width - width) / 2,
+                room.y1 + (room_height - height) / 2,
+            );
+            // a vault that doesn't match its legend is left out
+            if let Err(e) = prefabs.stamp(mb, vault, &rows, at, rng) {
+                mb.build_error = Some(MapError::BadPrefab(e));
+            }
+        }
+    }
+}
+
+// copies one half of the map over the other, follow with CullUnreachable and RecomputeStarts
+pub struct Mirror {
+    symmetry: Symmetry,
+}
+
+impl Mirror {
+    // flip is given a point and returns where it lands on the other side
+    fn copy_half(
+        mb: &mut MapBuilder,
+        in_source: impl Fn(Point) -> bool,
+        flip: impl Fn(Point) -> Point,
+    ) {
+        for y in 0..mb.map.height {
+            for x in 0..mb.map.width {
+                let p = Point::new(x, y);
+                if !in_source(p) {
+                    let from = mb.map.point2d_to_index(flip(p));
+                    let to = mb.map.point2d_to_index(p);
+                    mb.map.tiles[to] = mb.map.tiles[from];
+                }
+            }
+        }
+
+        let mut spawns: Vec<Point> = mb
+            .monster_spawns
+            .iter()
+            .copied()
+            .filter(|p| in_source(*p))
+            .collect();
+        let mirrored: Vec<Point> = spawns
+            .iter()
+            .map(|p| flip(*p))
+            .filter(|p| !in_source(*p))
+            .collect();
+        spawns.extend(mirrored);
+        mb.monster_spawns = spawns;
+
+        let mut entities: Vec<(Point, String)> = mb
+            .entity_spawns
+            .iter()
+            .filter(|(p, _)| in_source(*p))
+            .cloned()
+            .collect();
+        let mirrored: Vec<(Point, String)> = entities
+            .iter()
+            .map(|(p, name)| (flip(*p), name.clone()))
+            .filter(|(p, _)| !in_source(*p))
+            .collect();
+        entities.extend(mirrored);
+        mb.entity_spawns = entities;
+
+        // rooms wholly in the source half are copied, the rest are kept as they were
+        let copies: Vec<Rect> = mb
+            .rooms
+            .iter()
+            .filter(|r| {
+                in_source(Point::new(r.x1, r.y1)) && in_source(Point::new(r.x2 - 1, r.y2 - 1))
+            })
+            .map(|r| {
+                let (a, b) = (
+                    flip(Point::new(r.x1, r.y1)),
+                    flip(Point::new(r.x2 - 1, r.y2 - 1)),
+                );
+                Rect::with_exact(
+                    a.x.min(b.x),
+                    a.y.min(b.y),
+                    a.x.max(b.x) + 1,
+                    a.y.max(b.y) + 1,
+                )
+            })
+            .filter(|r| !in_source(Point::new(r.x1, r.y1)))
+            .collect();
+        mb.rooms.extend(copies);
+
+        keep_start_on_floor(mb);
+    }
+}
+
+impl MapPostProcessor for Mirror {
+    fn apply(&self, mb: &mut MapBuilder, _rng: &mut RandomNumberGenerator) {
+        let (width, height) = (mb.map.width, mb.map.height);
+        if matches!(self.symmetry, Symmetry::Horizontal | Symmetry::Both) {
+            Self::copy_half(
+                mb,
+                |p| p.x <= width - 1 - p.x,
+                |p| Point::new(width - 1 - p.x, p.y),
+            );
+        }
+        if matches!(self.symmetry, Symmetry::Vertical | Symmetry::Both) {
+            Self::copy_half(
+                mb,
+                |p| p.y <= height - 1 - p.y,
+                |p| Point::new(p.x, height - 1 - p.y),
+            );
+        }
+    }
+}
+
+// one tile wide corridors get a second lane, right or below
+pub struct WidenCorridors {}
+
+impl MapPostProcessor for WidenCorridors {
+    fn apply(&self, mb: &mut MapBuilder, _rng: &mut RandomNumberGenerator) {
+        let map = &mb.map;
+        let mut new_tiles = map.tiles.clone();
+        for y in 1..map.height - 1 {
+            for x in 1..map.width - 1 {
+                if map.tiles[map.idx(x, y)] != TileType::Floor {
+                    continue;
+                }
+                let vertical = is_wall(map, x - 1, y) && is_wall(map, x + 1, y);
+                let horizontal = is_wall(map, x, y - 1) && is_wall(map, x, y + 1);
+                if vertical && !on_edge(map, x + 1, y) {
+                    new_tiles[map.idx(x + 1, y)] = TileType::Floor;
+                }
+                if horizontal && !on_edge(map, x, y + 1) {
+                    new_tiles[map.idx(x, y + 1)] = TileType::Floor;
+                }
+            }
+        }
+        mb.map.tiles = new_tiles;
+    }
+}
+
+// after steps that moved walls around, find a start, grail and spawns that still make sense
+pub struct RecomputeStarts {}
+
+impl MapPostProcessor for RecomputeStarts {
+    fn apply(&self, mb: &mut MapBuilder, _rng: &mut RandomNumberGenerator) {
+        if !keep_start_on_floor(mb) {
+            return;
+        }
+        mb.grail_start = mb.find_most_distant().unwrap_or(mb.player_start);
+
+        let dijkstra_map = distances_from(&mb.map, mb.player_start);
+        let (start, grail, map) = (mb.player_start, mb.grail_start, &mb.map);
+        let keep = |p: &Point| {
+            map.can_enter_tile(*p)
+                && dijkstra_map.map[map.point2d_to_index(*p)] < f32::MAX
+                && *p != start
+                && *p != grail
+        };
+        mb.monster_spawns.retain(|p| keep(p));
+        mb.entity_spawns.retain(|(p, _)| keep(p));
+    }
+}
@@ -1,87 +1,399 @@
+use super::rooms::RoomsArchitect;
+use super::{MapArchitect, distances_from};
 use crate::prelude::*;
+use crate::spawner::open_resource;
+use ron::de::from_reader;
+use serde::Deserialize;
+use std::sync::Arc;
 
-// variables representing the prefab layout, width, and height
-const FORTRESS: (&str, i32, i32) = (
-    "
-------------
----######---
----#----#---
----#-M--#---
--###----###-
---M------M--
--###----###-
----#----#---
----#----#---
----######---
-------------
-",
-    12,
-    11,
-);
-
-pub fn apply_prefab(mb: &mut MapBuilder, rng: &mut RandomNumberGenerator) {
-    let mut placement = None;
+// how far from the player a sectional prefab has to start, so it isn't dropped on top of them
+const MIN_SECTIONAL_DISTANCE: f32 = 20.0;
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum PrefabKind {
+    Level,     // replaces the architect's map entirely
+    Vault,     // stamped inside a room that is big enough
+    Sectional, // laid over any part of the map
+}
+
+// what a character in a layout turns into
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub enum LegendEntry {
+    Floor,
+    Wall,
+    Door,
+    LockedDoor, // needs a key to open
+    Exit,       // where the way down, or the grail, goes
+    PlayerStart,
+    Spawn,          // a random monster or item for the level
+    Entity(String), // a named template from template.ron
+    Tile(TileType), // any other tile, water, lava and the like
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Prefab {
+    pub name: String,
+    pub kind: PrefabKind,
+    pub layout: String,
+    // added to the shared legend, and wins over it
+    #[serde(default)]
+    pub legend: Vec<(char, LegendEntry)>,
+    #[serde(default)]
+    pub min_level: u32,
+    pub max_level: Option<u32>,
+    #[serde(default = "default_frequency")]
+    pub frequency: u32,
+    #[serde(default)]
+    pub rotate: bool,
+    #[serde(default)]
+    pub mirror: bool,
+    // what each M in the layout becomes, template names with weights,
+    // left empty M draws from the level's usual spawns
+    #[serde(default)]
+    pub spawns: Vec<(String, u32)>,
+}
+
+fn default_frequency() -> u32 {
+    1
+}
+
+// top level collection representing resources/prefabs.ron
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct Prefabs {
+    pub legend: Vec<(char, LegendEntry)>,
+    pub prefabs: Vec<Prefab>,
+}
+
+impl Prefab {
+    pub fn rows(&self) -> Vec<Vec<char>> {
+        self.layout
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| line.chars().collect())
+            .collect()
+    }
+
+    pub fn width(&self) -> i32 {
+        self.rows().iter().map(Vec::len).max().unwrap_or(0) as i32
+    }
+
+    pub fn height(&self) -> i32 {
+        self.rows().len() as i32
+    }
+
+    pub fn fits_level(&self, level: u32) -> bool {
+        self.min_level <= level && self.max_level.is_none_or(|max| level <= max)
+    }
+
+    // the layout turned and flipped at random, as far as the prefab allows
+    pub fn oriented(&self, rng: &mut RandomNumberGenerator) -> Vec<Vec<char>> {
+        let mut rows = self.rows();
+        if self.rotate {
+            for _ in 0..rng.range(0, 4) {
+                rows = rotate(&rows);
+            }
+        }
+        if self.mirror && rng.range(0, 2) == 1 {
+            rows.iter_mut().for_each(|row| row.reverse());
+        }
+        rows
+    }
+
+    // a name from the prefab's own spawn table, None when it doesn't have one
+    pub fn roll_spawn(&self, rng: &mut RandomNumberGenerator) -> Option<String> {
+        // a single entry doesn't use up a random number
+        if self.spawns.len() < 2 {
+            return self.spawns.first().map(|(name, _)| name.clone());
+        }
+        let total: u32 = self.spawns.iter().map(|(_, weight)| weight).sum();
+        let mut roll = rng.range(0, total);
+        for (name, weight) in self.spawns.iter() {
+            if roll < *weight {
+                return Some(name.clone());
+            }
+            roll -= weight;
+        }
+        None
+    }
+}
+
+pub(super) fn size_of(rows: &[Vec<char>]) -> (i32, i32) {
+    (
+        rows.iter().map(Vec::len).max().unwrap_or(0) as i32,
+        rows.len() as i32,
+    )
+}
+
+// a quarter turn clockwise, short rows are padded with floor
+fn rotate(rows: &[Vec<char>]) -> Vec<Vec<char>> {
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    (0..width)
+        .map(|x| {
+            rows.iter()
+                .rev()
+                .map(|row| row.get(x).copied().unwrap_or('-'))
+                .collect()
+        })
+        .collect()
+}
 
-    let dijkstra_map = DijkstraMap::new(
-        SCREEN_WIDTH,
-        SCREEN_HEIGHT,
-        &vec![mb.map.point2d_to_index(mb.player_start)],
-        &mb.map,
-        1024.0,
-    );
+impl Prefabs {
+    pub fn load() -> Self {
+        let file =
+            open_resource("resources/prefabs.ron").expect("Failed to open resources/prefabs.ron");
+        let prefabs: Self = from_reader(file).expect("Failed to parse prefabs.ron");
+        if let Err(e) = prefabs.check(&Templates::load()) {
+            panic!("Invalid prefabs.ron: {}", e);
+        }
+        prefabs
+    }
 
+    // every character has a meaning and every named entity has a template
+    pub fn check(&self, templates: &Templates) -> Result<(), String> {
+        for prefab in self.prefabs.iter() {
+            if prefab.height() == 0 {
+                return Err(format!("prefab {} has an empty layout", prefab.name));
+            }
+            for c in prefab.rows().iter().flatten() {
+                match self.lookup(prefab, *c) {
+                    None => {
+                        return Err(format!(
+                            "prefab {} uses {:?}, which isn't in a legend",
+                            prefab.name, c
+                        ));
+                    }
+                    Some(LegendEntry::Entity(name))
+                        if !templates.entities.iter().any(|t| t.name == *name) =>
+                    {
+                        return Err(format!(
+                            "prefab {} places unknown entity {}",
+                            prefab.name, name
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+            for (name, _) in prefab.spawns.iter() {
+                if !templates.entities.iter().any(|t| t.name == *name) {
+                    return Err(format!(
+                        "prefab {} spawns unknown entity {}",
+                        prefab.name, name
+                    ));
+                }
+            }
+            if !prefab.spawns.is_empty() && prefab.spawns.iter().all(|(_, weight)| *weight == 0) {
+                return Err(format!(
+                    "prefab {} has spawns that all weigh 0",
+                    prefab.name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn lookup<'a>(&'a self, prefab: &'a Prefab, c: char) -> Option<&'a LegendEntry> {
+        prefab
+            .legend
+            .iter()
+            .chain(self.legend.iter())
+            .find(|(key, _)| *key == c)
+            .map(|(_, entry)| entry)
+    }
+
+    // weighted by frequency, among prefabs of the kind allowed on this level
+    pub fn choose<'a>(
+        &'a self,
+        kind: PrefabKind,
+        level: u32,
+        rng: &mut RandomNumberGenerator,
+        fits: impl Fn(&Prefab) -> bool,
+    ) -> Option<&'a Prefab> {
+        let candidates: Vec<&Prefab> = self
+            .prefabs
+            .iter()
+            .filter(|p| p.kind == kind && p.fits_level(level) && p.frequency > 0 && fits(p))
+            .collect();
+        // a single candidate doesn't use up a random number, keeping older seeds stable
+        if candidates.len() < 2 {
+            return candidates.first().copied();
+        }
+        let total: u32 = candidates.iter().map(|p| p.frequency).sum();
+        let mut roll = rng.range(0, total);
+        for prefab in candidates {
+            if roll < prefab.frequency {
+                return Some(prefab);
+            }
+            roll -= prefab.frequency;
+        }
+        None
+    }
+
+    // writes the layout onto the map with its top left corner at `at`
+    // the map is left alone if a character isn't in a legend
+    pub fn stamp(
+        &self,
+        mb: &mut MapBuilder,
+        prefab: &Prefab,
+        rows: &[Vec<char>],
+        at: Point,
+        rng: &mut RandomNumberGenerator,
+    ) -> Result<(), String> {
+        if let Some(c) = rows
+            .iter()
+            .flatten()
+            .find(|c| self.lookup(prefab, **c).is_none())
+        {
+            return Err(format!(
+                "prefab {} uses {:?}, which isn't in a legend",
+                prefab.name, c
+            ));
+        }
+        let (width, height) = size_of(rows);
+        let area = Rect::with_size(at.x, at.y, width, height);
+        // erase any random monster spawns in the area
+        mb.monster_spawns.retain(|p| !area.point_in_rect(*p));
+        mb.entity_spawns.retain(|(p, _)| !area.point_in_rect(*p));
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.iter().enumerate() {
+                let p = Point::new(at.x + x as i32, at.y + y as i32);
+                let Some(idx) = mb.map.try_idx(p) else {
+                    continue;
+                };
+                let Some(entry) = self.lookup(prefab, *c) else {
+                    continue;
+                };
+                mb.map.tiles[idx] = match entry {
+                    LegendEntry::Wall => TileType::Wall,
+                    LegendEntry::Door => TileType::Door,
+                    LegendEntry::LockedDoor => TileType::LockedDoor,
+                    LegendEntry::Tile(tile) => *tile,
+                    _ => TileType::Floor,
+                };
+                match entry {
+                    LegendEntry::Exit => mb.grail_start = p,
+                    LegendEntry::PlayerStart => mb.player_start = p,
+                    LegendEntry::Spawn => match prefab.roll_spawn(rng) {
+                        Some(name) => mb.entity_spawns.push((p, name)),
+                        None => mb.monster_spawns.push(p),
+                    },
+                    LegendEntry::Entity(name) => mb.entity_spawns.push((p, name.clone())),
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// lays one sectional prefab somewhere reachable but away from the player
+pub fn apply_prefab(prefabs: &Prefabs, mb: &mut MapBuilder, rng: &mut RandomNumberGenerator) {
+    let (map_width, map_height) = (mb.map.width, mb.map.height);
+    // small maps may not have room for any of them
+    let Some(prefab) = prefabs.choose(PrefabKind::Sectional, mb.level, rng, |p| {
+        p.width().max(p.height()) < map_width.min(map_height)
+    }) else {
+        return;
+    };
+    let rows = prefab.oriented(rng);
+    let (width, height) = size_of(&rows);
+
+    let dijkstra_map = distances_from(&mb.map, mb.player_start);
+
+    let mut placement = None;
     let mut attempts = 0;
     while placement.is_none() && attempts < 10 {
         // create rect with size of prefab
         let dimensions = Rect::with_size(
-            rng.range(0, SCREEN_WIDTH - FORTRESS.1),
-            rng.range(0, SCREEN_HEIGHT - FORTRESS.2),
-            FORTRESS.1,
-            FORTRESS.2,
+            rng.range(0, mb.map.width - width),
+            rng.range(0, mb.map.height - height),
+            width,
+            height,
         );
         let mut can_place = false;
         dimensions.for_each(|p| {
             let idx = mb.map.point2d_to_index(p);
             let distance = dijkstra_map.map[idx];
-            // check if tile is floor and far enough from player start and not on grail start
-            if distance < 2000.0 && distance > 20.0 && mb.grail_start != p {
+            // check if tile is reachable and far enough from player start and not on grail start
+            if distance < f32::MAX && distance > MIN_SECTIONAL_DISTANCE && mb.grail_start != p {
                 can_place = true;
             }
         });
-        // erase any random monster spawns in the area
-        if can_place {
+        let points = dimensions.point_set();
+        // never build over the player or the grail, both have to stay reachable
+        if can_place && !points.contains(&mb.player_start) && !points.contains(&mb.grail_start) {
             placement = Some(Point::new(dimensions.x1, dimensions.y1));
-            let points = dimensions.point_set();
-            mb.monster_spawns.retain(|p| !points.contains(p));
         }
         attempts += 1;
     }
 
-    // apply prefab to map at placement point
     if let Some(placement) = placement {
-        let string_vec: Vec<char> = FORTRESS
-            .0
-            // remove newlines and carriage returns
-            .chars()
-            .filter(|c| *c != '\r' && *c != '\n')
-            .collect();
-        let mut i = 0;
-        for ty in placement.y..placement.y + FORTRESS.2 {
-            for tx in placement.x..placement.x + FORTRESS.1 {
-                let idx = map_idx(tx, ty);
-                let c = string_vec[i];
-                match c {
-                    '-' => mb.map.tiles[idx] = TileType::Floor,
-                    '#' => mb.map.tiles[idx] = TileType::Wall,
-                    'M' => {
-                        // monsters need a floor tile
-                        mb.map.tiles[idx] = TileType::Floor;
-                        mb.monster_spawns.push(Point::new(tx, ty));
-                    }
-                    _ => println!("Unknown char in prefab: {}", c),
-                }
-                i += 1;
-            }
+        // a prefab that doesn't match its legend is left out
+        if let Err(e) = prefabs.stamp(mb, prefab, &rows, placement, rng) {
+            mb.build_error = Some(MapError::BadPrefab(e));
         }
     }
 }
+
+// a whole floor from a Level prefab, centred on a map of walls
+#[derive(Default)]
+pub struct PrefabLevelArchitect {
+    level: u32,
+    prefabs: Arc<Prefabs>,
+}
+
+impl MapArchitect for PrefabLevelArchitect {
+    fn set_level(&mut self, level: u32) {
+        self.level = level;
+    }
+
+    fn set_prefabs(&mut self, prefabs: &Arc<Prefabs>) {
+        self.prefabs = Arc::clone(prefabs);
+    }
+
+    fn new(&mut self, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> MapBuilder {
+        let prefabs = Arc::clone(&self.prefabs);
+        let Some(prefab) = prefabs.choose(PrefabKind::Level, self.level, rng, |p| {
+            p.width().max(p.height()) <= width.min(height)
+        }) else {
+            // nothing drawn for this floor, fall back to plain rooms
+            return RoomsArchitect {}.new(rng, width, height);
+        };
+
+        let mut mb = MapBuilder::blank(width, height);
+        mb.fill(TileType::Wall);
+        let rows = prefab.oriented(rng);
+        let (prefab_width, prefab_height) = size_of(&rows);
+        let at = Point::new((width - prefab_width) / 2, (height - prefab_height) / 2);
+        if let Err(e) = prefabs.stamp(&mut mb, prefab, &rows, at, rng) {
+            let mut mb = RoomsArchitect {}.new(rng, width, height);
+            mb.build_error = Some(MapError::BadPrefab(e));
+            return mb;
+        }
+
+        // layouts without an @ or > get them worked out like any other map
+        let has = |wanted: LegendEntry| {
+            rows.iter()
+                .flatten()
+                .any(|c| prefabs.lookup(prefab, *c) == Some(&wanted))
+        };
+        if !has(LegendEntry::PlayerStart) {
+            let center = Point::new(width / 2, height / 2);
+            mb.player_start = mb
+                .map
+                .tiles
+                .iter()
+                .enumerate()
+                .filter(|(_, t)| **t == TileType::Floor)
+                .map(|(idx, _)| mb.map.index_to_point2d(idx))
+                .min_by_key(|p| (p.x - center.x).abs() + (p.y - center.y).abs())
+                .unwrap_or(center);
+        }
+        if !has(LegendEntry::Exit) {
+            mb.grail_start = mb.find_most_distant().unwrap_or(mb.player_start);
+        }
+
+        mb
+    }
+}
@@ -0,0 +1,117 @@
+use super::MapArchitect;
+use super::automata::CellularAutomataArchitect;
+use super::bsp::BspArchitect;
+use super::drunkard::DrunkardWalkArchitect;
+use super::empty::EmptyArchitect;
+use super::prefab::PrefabLevelArchitect;
+use super::rooms::RoomsArchitect;
+use super::voronoi::VoronoiArchitect;
+use super::wfc::WaveFunctionCollapseArchitect;
+use crate::prelude::*;
+
+pub type ArchitectFactory = fn() -> Box<dyn MapArchitect>;
+
+struct RegisteredArchitect {
+    name: &'static str,
+    default_weight: u32, // share of the mix when a floor doesn't list its own weights
+    factory: ArchitectFactory,
+}
+
+// every architect a campaign can name, looked up by name and picked by weight
+pub struct ArchitectRegistry {
+    architects: Vec<RegisteredArchitect>,
+}
+
+impl ArchitectRegistry {
+    // the architects that ship with the game, same as standard
+    pub fn new() -> Self {
+        Self::standard()
+    }
+
+    // nothing registered, for building a set up from scratch
+    pub fn empty() -> Self {
+        Self {
+            architects: Vec::new(),
+        }
+    }
+
+    // the architects that ship with the game
+    // architects without a default weight are only built when forced or given a weight
+    pub fn standard() -> Self {
+        let mut registry = Self::empty();
+        registry.register("automata", 1, || Box::new(CellularAutomataArchitect {}));
+        registry.register("drunkard", 1, || Box::new(DrunkardWalkArchitect {}));
+        registry.register("rooms", 1, || Box::new(RoomsArchitect {}));
+        registry.register("bsp", 1, || Box::new(BspArchitect {}));
+        registry.register("voronoi", 1, || Box::new(VoronoiArchitect {}));
+        // slower than the others, so only used where a floor asks for it
+        registry.register("wfc", 0, || {
+            Box::new(WaveFunctionCollapseArchitect::default())
+        });
+        // whole floors drawn in prefabs.ron
+        registry.register("prefab", 0, || Box::new(PrefabLevelArchitect::default()));
+        // empty is a debug map
+        registry.register("empty", 0, || Box::new(EmptyArchitect {}));
+        registry
+    }
+
+    // registering a name twice replaces the earlier architect
+    pub fn register(&mut self, name: &'static str, default_weight: u32, factory: ArchitectFactory) {
+        self.architects.retain(|a| a.name != name);
+        self.architects.push(RegisteredArchitect {
+            name,
+            default_weight,
+            factory,
+        });
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.architects.iter().map(|a| a.name).collect()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.architects.iter().any(|a| a.name == name)
+    }
+
+    pub fn create(&self, name: &str) -> Option<Box<dyn MapArchitect>> {
+        self.architects
+            .iter()
+            .find(|a| a.name == name)
+            .map(|a| (a.factory)())
+    }
+
+    // the mix used by Floor::any, architects with no default weight are left out
+    pub fn default_weights(&self) -> Vec<(String, u32)> {
+        self.architects
+            .iter()
+            .filter(|a| a.default_weight > 0)
+            .map(|a| (a.name.to_string(), a.default_weight))
+            .collect()
+    }
+
+    // None if the weights add up to nothing or pick a name that isn't registered
+    pub fn choose(
+        &self,
+        rng: &mut RandomNumberGenerator,
+        weights: &[(String, u32)],
+    ) -> Option<Box<dyn MapArchitect>> {
+        let total: u32 = weights.iter().map(|(_, weight)| weight).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut roll = rng.range(0, total);
+        for (name, weight) in weights {
+            if roll < *weight {
+                return self.create(name);
+            }
+            roll -= weight;
+        }
+        None
+    }
+}
+
+impl Default for ArchitectRegistry {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
@@ -4,20 +4,13 @@ use crate::prelude::*;
 pub struct RoomsArchitect {}
 
 impl MapArchitect for RoomsArchitect {
-    fn new(&mut self, rng: &mut RandomNumberGenerator) -> MapBuilder {
-        let mut mb = MapBuilder {
-            map: Map::new(),
-            rooms: Vec::new(),
-            monster_spawns: Vec::new(),
-            player_start: Point::zero(),
-            grail_start: Point::zero(),
-            theme: super::themes::DungeonTheme::new(),
-        };
+    fn new(&mut self, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> MapBuilder {
+        let mut mb = MapBuilder::blank(width, height);
         mb.fill(TileType::Wall);
         mb.build_random_rooms(rng);
         mb.build_corridors(rng);
         mb.player_start = mb.rooms[0].center();
-        mb.grail_start = mb.find_most_distant();
+        mb.grail_start = mb.find_most_distant().unwrap_or(mb.player_start);
         for room in mb.rooms.iter().skip(1) {
             mb.monster_spawns.push(room.center());
         }
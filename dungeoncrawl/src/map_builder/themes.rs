@@ -3,17 +3,31 @@ use crate::prelude::*;
 pub struct DungeonTheme {}
 
 impl DungeonTheme {
+    #[allow(clippy::new_ret_no_self)] // boxed, themes are only used behind MapTheme
     pub fn new() -> Box<dyn MapTheme> {
         Box::new(Self {})
     }
 }
 
 impl MapTheme for DungeonTheme {
+    fn name(&self) -> &'static str {
+        "dungeon"
+    }
+
     fn tile_to_render(&self, tile_type: TileType) -> FontCharType {
         match tile_type {
             TileType::Floor => to_cp437('.'),
             TileType::Wall => to_cp437('#'),
             TileType::Exit => to_cp437('>'),
+            TileType::UpStairs => to_cp437('<'),
+            TileType::Door => to_cp437('+'),
+            TileType::OpenDoor => to_cp437('\''),
+            TileType::LockedDoor => to_cp437('='),
+            TileType::ShallowWater => to_cp437('~'),
+            TileType::DeepWater => to_cp437('≈'),
+            TileType::Lava => to_cp437('▒'),
+            TileType::Rubble => to_cp437(':'),
+            TileType::Bridge => to_cp437('═'),
         }
     }
 }
@@ -21,17 +35,43 @@ impl MapTheme for DungeonTheme {
 pub struct ForestTheme {}
 
 impl ForestTheme {
+    #[allow(clippy::new_ret_no_self)] // boxed, themes are only used behind MapTheme
     pub fn new() -> Box<dyn MapTheme> {
         Box::new(Self {})
     }
 }
 
 impl MapTheme for ForestTheme {
+    fn name(&self) -> &'static str {
+        "forest"
+    }
+
     fn tile_to_render(&self, tile_type: TileType) -> FontCharType {
         match tile_type {
             TileType::Floor => to_cp437(';'),
             TileType::Wall => to_cp437('"'),
             TileType::Exit => to_cp437('>'),
+            TileType::UpStairs => to_cp437('<'),
+            TileType::Door => to_cp437('+'),
+            TileType::OpenDoor => to_cp437('\''),
+            TileType::LockedDoor => to_cp437('='),
+            TileType::ShallowWater => to_cp437('~'),
+            TileType::DeepWater => to_cp437('≈'),
+            TileType::Lava => to_cp437('▒'),
+            TileType::Rubble => to_cp437(':'),
+            TileType::Bridge => to_cp437('═'),
         }
     }
 }
+
+// names used by campaign.ron to pick themes
+pub const THEME_NAMES: [&str; 2] = ["dungeon", "forest"];
+
+// look up a theme by the name it reports, used when restoring a saved game
+pub fn theme_by_name(name: &str) -> Option<Box<dyn MapTheme>> {
+    match name {
+        "dungeon" => Some(DungeonTheme::new()),
+        "forest" => Some(ForestTheme::new()),
+        _ => None,
+    }
+}
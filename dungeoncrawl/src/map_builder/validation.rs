@@ -0,0 +1,184 @@
+use super::post_process::keep_start_on_floor;
+use super::{cull_unreachable, distances_from, distances_without_keys, is_open_tile};
+use crate::prelude::*;
+use std::fmt;
+
+// repair may wall off most of the map, below this share of open tiles it is rebuilt instead
+const MIN_REACHABLE_SHARE: f32 = 0.25;
+// the template placed to open locked doors
+const KEY: &str = "Key";
+
+// the first thing found wrong with a generated map
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapError {
+    StartBlocked(Point),
+    ExitAtStart,
+    ExitUnreachable(Point),
+    SpawnUnreachable(Point),
+    MissingKeys {
+        locked: usize,
+        keys: usize,
+    },
+    FloorUnreachable {
+        tiles: usize,
+    },
+    TooSmall {
+        reachable: usize,
+        open: usize,
+    },
+    GaveUp {
+        attempts: usize,
+        last: Box<MapError>,
+    },
+    BadPrefab(String),
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapError::StartBlocked(p) => write!(f, "Player start {:?} is not open floor", p),
+            MapError::ExitAtStart => write!(f, "Nowhere to put the exit away from the start"),
+            MapError::ExitUnreachable(p) => write!(f, "Exit {:?} can't be reached", p),
+            MapError::SpawnUnreachable(p) => write!(f, "Spawn point {:?} can't be reached", p),
+            MapError::MissingKeys { locked, keys } => write!(
+                f,
+                "{} locked doors but only {} keys can be reached",
+                locked, keys
+            ),
+            MapError::FloorUnreachable { tiles } => {
+                write!(f, "{} floor tiles can't be reached", tiles)
+            }
+            MapError::TooSmall { reachable, open } => write!(
+                f,
+                "Only {} of {} open tiles can be reached",
+                reachable, open
+            ),
+            MapError::GaveUp { attempts, last } => {
+                write!(f, "No valid map after {} attempts: {}", attempts, last)
+            }
+            MapError::BadPrefab(reason) => write!(f, "Left a prefab out, {}", reason),
+        }
+    }
+}
+
+impl MapBuilder {
+    // everything the player needs has to be reachable from player_start, opening doors on the way
+    pub fn validate(&self) -> Result<(), MapError> {
+        if !self.map.can_enter_tile(self.player_start) {
+            return Err(MapError::StartBlocked(self.player_start));
+        }
+        if self.grail_start == self.player_start {
+            return Err(MapError::ExitAtStart);
+        }
+
+        let dijkstra_map = distances_from(&self.map, self.player_start);
+        // unreachable tiles keep the starting value
+        let reachable = |p: Point| {
+            self.map
+                .try_idx(p)
+                .is_some_and(|idx| dijkstra_map.map[idx] < f32::MAX)
+        };
+
+        if !reachable(self.grail_start) {
+            return Err(MapError::ExitUnreachable(self.grail_start));
+        }
+        let spawns = self
+            .monster_spawns
+            .iter()
+            .chain(self.entity_spawns.iter().map(|(p, _)| p));
+        for p in spawns {
+            if !reachable(*p) {
+                return Err(MapError::SpawnUnreachable(*p));
+            }
+        }
+        // a key is used up by its door, so every one has to be found without opening any
+        let locked = self.locked_doors();
+        let keys = self.keys_before_locked_doors();
+        if keys < locked {
+            return Err(MapError::MissingKeys { locked, keys });
+        }
+        let unreachable = (0..self.map.tiles.len())
+            .map(|idx| self.map.index_to_point2d(idx))
+            .filter(|p| is_open_tile(&self.map, *p) && !reachable(*p))
+            .count();
+        if unreachable > 0 {
+            return Err(MapError::FloorUnreachable { tiles: unreachable });
+        }
+        Ok(())
+    }
+
+    // walls off what can't be reached and moves whatever was left there, then checks again
+    pub fn repair(&mut self) -> Result<(), MapError> {
+        if !keep_start_on_floor(self) {
+            return Err(MapError::StartBlocked(self.player_start));
+        }
+        let open = (0..self.map.tiles.len())
+            .filter(|idx| is_open_tile(&self.map, self.map.index_to_point2d(*idx)))
+            .count();
+        let reachable = cull_unreachable(&mut self.map, self.player_start);
+        if (reachable as f32) < open as f32 * MIN_REACHABLE_SHARE {
+            return Err(MapError::TooSmall { reachable, open });
+        }
+
+        let map = &self.map;
+        self.monster_spawns.retain(|p| map.can_enter_tile(*p));
+        self.entity_spawns.retain(|(p, _)| map.can_enter_tile(*p));
+        if !map.can_enter_tile(self.grail_start) {
+            self.grail_start = self.find_most_distant().unwrap_or(self.player_start);
+        }
+        self.place_missing_keys();
+        self.validate()
+    }
+
+    fn locked_doors(&self) -> usize {
+        self.map
+            .tiles
+            .iter()
+            .filter(|t| **t == TileType::LockedDoor)
+            .count()
+    }
+
+    fn keys_before_locked_doors(&self) -> usize {
+        let dijkstra_map = distances_without_keys(&self.map, self.player_start);
+        self.entity_spawns
+            .iter()
+            .filter(|(p, name)| {
+                name == KEY && dijkstra_map.map[self.map.point2d_to_index(*p)] < f32::MAX
+            })
+            .count()
+    }
+
+    // missing keys go on the free floor furthest from the start that no locked door is in the way of
+    fn place_missing_keys(&mut self) {
+        let missing = self
+            .locked_doors()
+            .saturating_sub(self.keys_before_locked_doors());
+        if missing == 0 {
+            return;
+        }
+        let dijkstra_map = distances_without_keys(&self.map, self.player_start);
+        let taken: Vec<Point> = self
+            .monster_spawns
+            .iter()
+            .chain(self.entity_spawns.iter().map(|(p, _)| p))
+            .chain([self.player_start, self.grail_start].iter())
+            .copied()
+            .collect();
+        let mut free: Vec<(usize, f32)> = dijkstra_map
+            .map
+            .iter()
+            .enumerate()
+            .filter(|(idx, distance)| {
+                **distance < f32::MAX
+                    && self.map.tiles[*idx] == TileType::Floor
+                    && !taken.contains(&self.map.index_to_point2d(*idx))
+            })
+            .map(|(idx, distance)| (idx, *distance))
+            .collect();
+        free.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        for (idx, _) in free.into_iter().take(missing) {
+            let p = self.map.index_to_point2d(idx);
+            self.entity_spawns.push((p, KEY.to_string()));
+        }
+    }
+}
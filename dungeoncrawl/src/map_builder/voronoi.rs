@@ -0,0 +1,109 @@
+use super::{MapArchitect, cull_unreachable};
+use crate::prelude::*;
+use std::collections::BTreeMap;
+
+// roughly one cell for every this many tiles, so bigger maps get more cells
+const TILES_PER_CELL: i32 = 125;
+
+pub struct VoronoiArchitect {}
+
+impl MapArchitect for VoronoiArchitect {
+    fn new(&mut self, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> MapBuilder {
+        let mut mb = MapBuilder::blank(width, height);
+        let seeds = self.seed_cells(rng, width, height);
+        let cells = self.assign_cells(&mb.map, &seeds);
+        self.build_walls(&mut mb.map, &cells);
+        self.open_doors(rng, &mut mb.map, &cells);
+
+        let start = MapBuilder::central_floor(&mb.map).unwrap_or(Point::new(width / 2, height / 2));
+        // cells squeezed too thin to hold a door are walled up
+        cull_unreachable(&mut mb.map, start);
+        mb.monster_spawns = mb.spawn_monsters(&start, rng);
+        mb.player_start = start;
+        mb.grail_start = mb.find_most_distant().unwrap_or(mb.player_start);
+
+        mb
+    }
+}
+
+impl VoronoiArchitect {
+    fn seed_cells(&self, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> Vec<Point> {
+        let count = ((width * height) / TILES_PER_CELL).max(2);
+        (0..count)
+            .map(|_| Point::new(rng.range(1, width - 1), rng.range(1, height - 1)))
+            .collect()
+    }
+
+    // every tile belongs to the cell whose seed is nearest
+    fn assign_cells(&self, map: &Map, seeds: &[Point]) -> Vec<usize> {
+        (0..map.tiles.len())
+            .map(|idx| {
+                let point = map.index_to_point2d(idx);
+                seeds
+                    .iter()
+                    .enumerate()
+                    .map(|(cell, seed)| {
+                        (
+                            cell,
+                            DistanceAlg::PythagorasSquared.distance2d(point, *seed),
+                        )
+                    })
+                    .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+                    .map(|(cell, _)| cell)
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    // a tile is wall where the cell changes to its right or below, giving one tile thick borders
+    fn build_walls(&self, map: &mut Map, cells: &[usize]) {
+        for y in 0..map.height {
+            for x in 0..map.width {
+                let idx = map.idx(x, y);
+                let on_edge = x == 0 || y == 0 || x == map.width - 1 || y == map.height - 1;
+                let border = (x + 1 < map.width && cells[map.idx(x + 1, y)] != cells[idx])
+                    || (y + 1 < map.height && cells[map.idx(x, y + 1)] != cells[idx]);
+                map.tiles[idx] = if on_edge || border {
+                    TileType::Wall
+                } else {
+                    TileType::Floor
+                };
+            }
+        }
+    }
+
+    // knocks one gap through the wall between every pair of neighbouring cells
+    fn open_doors(&self, rng: &mut RandomNumberGenerator, map: &mut Map, cells: &[usize]) {
+        // BTreeMap keeps the order, and so the map, the same for a given seed
+        let mut candidates: BTreeMap<(usize, usize), Vec<usize>> = BTreeMap::new();
+        for y in 1..map.height - 1 {
+            for x in 1..map.width - 1 {
+                let idx = map.idx(x, y);
+                if map.tiles[idx] != TileType::Wall {
+                    continue;
+                }
+                for (a, b) in [
+                    (Point::new(x - 1, y), Point::new(x + 1, y)),
+                    (Point::new(x, y - 1), Point::new(x, y + 1)),
+                ] {
+                    let (a_idx, b_idx) = (map.point2d_to_index(a), map.point2d_to_index(b));
+                    let both_floor =
+                        map.tiles[a_idx] == TileType::Floor && map.tiles[b_idx] == TileType::Floor;
+                    if both_floor && cells[a_idx] != cells[b_idx] {
+                        let pair = (
+                            cells[a_idx].min(cells[b_idx]),
+                            cells[a_idx].max(cells[b_idx]),
+                        );
+                        candidates.entry(pair).or_default().push(idx);
+                    }
+                }
+            }
+        }
+
+        for doors in candidates.values() {
+            if let Some(door) = rng.random_slice_entry(doors) {
+                map.tiles[*door] = TileType::Floor;
+            }
+        }
+    }
+}
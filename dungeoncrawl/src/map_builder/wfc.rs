@@ -0,0 +1,323 @@
+use super::{MapArchitect, cull_unreachable};
+use crate::prelude::*;
+use std::sync::Arc;
+
+// patterns are PATTERN_SIZE square pieces of the samples, overlapping by all but a row or column
+const PATTERN_SIZE: usize = 3;
+// possible patterns for a tile are kept in a u128, rarer patterns beyond that are dropped
+const MAX_PATTERNS: usize = 128;
+// contradictions start the collapse again, after this many the best attempt is used
+const MAX_ATTEMPTS: usize = 10;
+const DIRECTIONS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+// samples use the prefab format, '#' is wall and anything else is floor
+const HALLS: &str = "
+#########
+#---#---#
+#-------#
+#---#---#
+##-###-##
+#---#---#
+#-------#
+#---#---#
+#########
+";
+
+const CAVERN: &str = "
+##########
+###--#####
+##-----###
+#-------##
+#--##----#
+##-##---##
+#----#--##
+##------##
+####--####
+##########
+";
+
+// what the samples allow: every pattern, how often it appeared, and which may sit next to which
+struct Model {
+    patterns: Vec<Vec<TileType>>,
+    weights: Vec<u32>,
+    compatible: Vec<[u128; 4]>, // per pattern and direction, the patterns allowed there
+}
+
+#[derive(Default)]
+pub struct WaveFunctionCollapseArchitect {
+    prefabs: Arc<Prefabs>,
+}
+
+impl MapArchitect for WaveFunctionCollapseArchitect {
+    fn set_prefabs(&mut self, prefabs: &Arc<Prefabs>) {
+        self.prefabs = Arc::clone(prefabs);
+    }
+
+    fn new(&mut self, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> MapBuilder {
+        let mut mb = MapBuilder::blank(width, height);
+        // every layout in prefabs.ron is a sample too
+        let mut samples: Vec<&str> = self
+            .prefabs
+            .prefabs
+            .iter()
+            .map(|p| p.layout.as_str())
+            .collect();
+        samples.extend([HALLS, CAVERN]);
+        let model = Self::learn(&samples);
+
+        // keep the attempt that left the most floor reachable
+        let mut best: Option<(usize, Map, Point)> = None;
+        for _ in 0..MAX_ATTEMPTS {
+            let (tiles, complete) = self.collapse(rng, &model, width, height);
+            let mut map = Map::new(width, height);
+            map.tiles = tiles;
+            self.wall_edges(&mut map);
+            let Some(start) = MapBuilder::central_floor(&map) else {
+                continue;
+            };
+            let reachable = cull_unreachable(&mut map, start);
+            if best.as_ref().is_none_or(|(most, _, _)| reachable > *most) {
+                best = Some((reachable, map, start));
+            }
+            if complete && reachable > (width * height) as usize / 5 {
+                break;
+            }
+        }
+
+        let (map, start) = match best {
+            Some((_, map, start)) => (map, start),
+            // nothing usable came out of the samples, an open room still makes a playable level
+            None => {
+                let mut map = Map::new(width, height);
+                self.wall_edges(&mut map);
+                (map, Point::new(width / 2, height / 2))
+            }
+        };
+        mb.map = map;
+        mb.monster_spawns = mb.spawn_monsters(&start, rng);
+        mb.player_start = start;
+        mb.grail_start = mb.find_most_distant().unwrap_or(mb.player_start);
+
+        mb
+    }
+}
+
+impl WaveFunctionCollapseArchitect {
+    fn parse_sample(text: &str) -> (usize, usize, Vec<TileType>) {
+        let rows: Vec<Vec<TileType>> = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                line.chars()
+                    .map(|c| {
+                        if c == '#' {
+                            TileType::Wall
+                        } else {
+                            TileType::Floor
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        // ragged rows are cut to the shortest one
+        let width = rows.iter().map(Vec::len).min().unwrap_or(0);
+        let tiles = rows
+            .iter()
+            .flat_map(|row| row[..width].iter().copied())
+            .collect();
+        (width, rows.len(), tiles)
+    }
+
+    fn rotate(pattern: &[TileType]) -> Vec<TileType> {
+        let n = PATTERN_SIZE;
+        (0..n * n)
+            .map(|i| pattern[(n - 1 - i % n) * n + i / n])
+            .collect()
+    }
+
+    fn reflect(pattern: &[TileType]) -> Vec<TileType> {
+        let n = PATTERN_SIZE;
+        (0..n * n)
+            .map(|i| pattern[(i / n) * n + (n - 1 - i % n)])
+            .collect()
+    }
+
+    // b placed at (dx, dy) from a has to agree with a wherever they overlap
+    fn agrees(a: &[TileType], b: &[TileType], dx: i32, dy: i32) -> bool {
+        let n = PATTERN_SIZE as i32;
+        for y in dy.max(0)..n.min(n + dy) {
+            for x in dx.max(0)..n.min(n + dx) {
+                if a[(y * n + x) as usize] != b[((y - dy) * n + (x - dx)) as usize] {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    fn learn(samples: &[&str]) -> Model {
+        let n = PATTERN_SIZE;
+        // a Vec rather than a HashMap so pattern order, and the map, only depend on the seed
+        let mut counted: Vec<(Vec<TileType>, u32)> = Vec::new();
+        for sample in samples {
+            let (width, height, tiles) = Self::parse_sample(sample);
+            if width < n || height < n {
+                continue;
+            }
+            for y in 0..=height - n {
+                for x in 0..=width - n {
+                    let mut pattern: Vec<TileType> = (0..n * n)
+                        .map(|i| tiles[(y + i / n) * width + x + i % n])
+                        .collect();
+                    // every rotation and reflection, so the samples don't need to cover them
+                    for _ in 0..4 {
+                        for variant in [pattern.clone(), Self::reflect(&pattern)] {
+                            match counted.iter_mut().find(|(p, _)| *p == variant) {
+                                Some((_, count)) => *count += 1,
+                                None => counted.push((variant, 1)),
+                            }
+                        }
+                        pattern = Self::rotate(&pattern);
+                    }
+                }
+            }
+        }
+        // stable sort keeps equally common patterns in the order they were found
+        counted.sort_by_key(|c| std::cmp::Reverse(c.1));
+        counted.truncate(MAX_PATTERNS);
+
+        let compatible = counted
+            .iter()
+            .map(|(a, _)| {
+                let mut allowed = [0u128; 4];
+                for (dir, (dx, dy)) in DIRECTIONS.iter().enumerate() {
+                    for (i, (b, _)) in counted.iter().enumerate() {
+                        if Self::agrees(a, b, *dx, *dy) {
+                            allowed[dir] |= 1 << i;
+                        }
+                    }
+                }
+                allowed
+            })
+            .collect();
+
+        Model {
+            weights: counted.iter().map(|(_, count)| *count).collect(),
+            patterns: counted.into_iter().map(|(p, _)| p).collect(),
+            compatible,
+        }
+    }
+
+    // returns the tiles and whether every tile was decided without a contradiction
+    // undecided tiles are left as wall
+    fn collapse(
+        &self,
+        rng: &mut RandomNumberGenerator,
+        model: &Model,
+        width: i32,
+        height: i32,
+    ) -> (Vec<TileType>, bool) {
+        let cells = (width * height) as usize;
+        let all = if model.patterns.len() == 128 {
+            u128::MAX
+        } else {
+            (1u128 << model.patterns.len()) - 1
+        };
+        let mut wave = vec![all; cells];
+        let mut complete = !model.patterns.is_empty();
+
+        while complete {
+            // the undecided cell with fewest options, scanning from a random cell to break ties
+            let offset = rng.range(0, cells);
+            let Some(cell) = (0..cells)
+                .map(|i| (i + offset) % cells)
+                .filter(|c| wave[*c].count_ones() > 1)
+                .min_by_key(|c| wave[*c].count_ones())
+            else {
+                break;
+            };
+
+            // more common patterns are picked more often
+            let options: Vec<usize> = (0..model.patterns.len())
+                .filter(|p| wave[cell] & (1 << p) != 0)
+                .collect();
+            let total: u32 = options.iter().map(|p| model.weights[*p]).sum();
+            let mut roll = rng.range(0, total);
+            let chosen = *options
+                .iter()
+                .find(|p| {
+                    if roll < model.weights[**p] {
+                        true
+                    } else {
+                        roll -= model.weights[**p];
+                        false
+                    }
+                })
+                .unwrap();
+            wave[cell] = 1 << chosen;
+            complete = self.propagate(model, &mut wave, width, height, cell);
+        }
+
+        let tiles = wave
+            .iter()
+            .map(|options| {
+                if options.count_ones() == 1 {
+                    model.patterns[options.trailing_zeros() as usize][0]
+                } else {
+                    TileType::Wall
+                }
+            })
+            .collect();
+        (tiles, complete)
+    }
+
+    // removes options neighbours can no longer take, false on a contradiction
+    fn propagate(
+        &self,
+        model: &Model,
+        wave: &mut [u128],
+        width: i32,
+        height: i32,
+        start: usize,
+    ) -> bool {
+        let mut stack = vec![start];
+        while let Some(cell) = stack.pop() {
+            let (x, y) = (cell as i32 % width, cell as i32 / width);
+            for (dir, (dx, dy)) in DIRECTIONS.iter().enumerate() {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || ny < 0 || nx >= width || ny >= height {
+                    continue;
+                }
+                let mut supported = 0u128;
+                let mut options = wave[cell];
+                while options != 0 {
+                    let p = options.trailing_zeros() as usize;
+                    supported |= model.compatible[p][dir];
+                    options &= options - 1;
+                }
+                let neighbour = (ny * width + nx) as usize;
+                let narrowed = wave[neighbour] & supported;
+                if narrowed == 0 {
+                    return false;
+                }
+                if narrowed != wave[neighbour] {
+                    wave[neighbour] = narrowed;
+                    stack.push(neighbour);
+                }
+            }
+        }
+        true
+    }
+
+    fn wall_edges(&self, map: &mut Map) {
+        for y in 0..map.height {
+            for x in 0..map.width {
+                if x == 0 || y == 0 || x == map.width - 1 || y == map.height - 1 {
+                    let idx = map.idx(x, y);
+                    map.tiles[idx] = TileType::Wall;
+                }
+            }
+        }
+    }
+}
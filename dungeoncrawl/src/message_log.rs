@@ -0,0 +1,26 @@
+// how many messages are kept, older ones are dropped
+const MAX_MESSAGES: usize = 5;
+
+// things the player should hear about that aren't on the map, the hud shows the newest
+#[derive(Default)]
+pub struct MessageLog {
+    messages: Vec<String>,
+}
+
+impl MessageLog {
+    pub fn add(&mut self, message: impl Into<String>) {
+        self.messages.push(message.into());
+        if self.messages.len() > MAX_MESSAGES {
+            self.messages.remove(0);
+        }
+    }
+
+    // oldest first
+    pub fn messages(&self) -> &[String] {
+        &self.messages
+    }
+
+    pub fn latest(&self) -> Option<&str> {
+        self.messages.last().map(String::as_str)
+    }
+}
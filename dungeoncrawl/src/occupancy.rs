@@ -0,0 +1,47 @@
+use crate::prelude::*;
+use std::collections::HashMap;
+
+// who is standing where, anything with health blocks its tile
+// rebuilt from the world before each round of moves and kept in step by the movement system
+#[derive(Default)]
+pub struct Occupancy {
+    at: HashMap<Point, Entity>,
+    // tiles the ai systems have already picked for a monster this turn
+    reserved: HashMap<Point, Entity>,
+}
+
+impl Occupancy {
+    pub fn rebuild(&mut self, blockers: impl IntoIterator<Item = (Entity, Point)>) {
+        self.at = blockers
+            .into_iter()
+            .map(|(entity, pos)| (pos, entity))
+            .collect();
+        self.reserved.clear();
+    }
+
+    pub fn occupant(&self, pos: Point) -> Option<Entity> {
+        self.at.get(&pos).copied()
+    }
+
+    // nobody standing there and nobody on their way
+    pub fn is_free(&self, pos: Point) -> bool {
+        !self.at.contains_key(&pos) && !self.reserved.contains_key(&pos)
+    }
+
+    // claims a tile for a move queued this turn, false if it is taken or already claimed
+    pub fn reserve(&mut self, entity: Entity, pos: Point) -> bool {
+        if !self.is_free(pos) {
+            return false;
+        }
+        self.reserved.insert(pos, entity);
+        true
+    }
+
+    // the tile left behind is free for the next mover straight away
+    pub fn step(&mut self, entity: Entity, from: Point, to: Point) {
+        if self.at.get(&from) == Some(&entity) {
+            self.at.remove(&from);
+            self.at.insert(to, entity);
+        }
+    }
+}
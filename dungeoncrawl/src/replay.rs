@@ -0,0 +1,152 @@
+use crate::prelude::*;
+use ron::ser::{PrettyConfig, to_string_pretty};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+// bump whenever Action or Replay change shape
+pub const REPLAY_VERSION: u32 = 3;
+pub const REPLAY_FILE: &str = "last_run.replay.ron";
+const FAST_FORWARD_TICKS: usize = 12;
+
+// the seed plus every action the game accepted, enough to play the run again
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: GameSeed,
+    pub architect: Option<String>, // forced architect, the run can't be rebuilt without it
+    pub diagonal_movement: bool,
+    pub actions: Vec<Action>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Replay")]
+struct ReplayHeader {
+    version: u32,
+}
+
+impl Replay {
+    // keeps the campaign settings that change how the run plays out
+    pub fn new(seed: GameSeed, campaign: &Campaign) -> Self {
+        Self {
+            version: REPLAY_VERSION,
+            seed,
+            architect: campaign.architect.clone(),
+            diagonal_movement: campaign.diagonal_movement,
+            actions: Vec::new(),
+        }
+    }
+
+    // puts the recorded settings back, so the run plays out as it did
+    pub fn apply_to(&self, campaign: &mut Campaign) {
+        campaign.architect = self.architect.clone();
+        campaign.diagonal_movement = self.diagonal_movement;
+    }
+
+    pub fn write(&self, path: &str) -> Result<(), SaveError> {
+        let contents = to_string_pretty(self, PrettyConfig::default())?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    pub fn read(path: &str) -> Result<Self, SaveError> {
+        let contents = fs::read_to_string(path)?;
+        let header: ReplayHeader = ron::de::from_str(&contents)?;
+        if header.version != REPLAY_VERSION {
+            return Err(SaveError::IncompatibleVersion {
+                found: header.version,
+                expected: REPLAY_VERSION,
+            });
+        }
+        Ok(ron::de::from_str(&contents)?)
+    }
+}
+
+// steps through a replay in the window, with pause, single step and fast forward
+pub struct ReplayPlayer {
+    replay: Replay,
+    next: usize,
+    pub paused: bool,
+    pub fast_forward: bool,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            next: 0,
+            paused: false,
+            fast_forward: false,
+        }
+    }
+
+    pub fn seed(&self) -> GameSeed {
+        self.replay.seed
+    }
+
+    pub fn next_action(&mut self) -> Option<Action> {
+        let action = self.replay.actions.get(self.next).copied();
+        if action.is_some() {
+            self.next += 1;
+        }
+        action
+    }
+
+    pub fn status(&self, keymap: &Keymap) -> String {
+        let mode = if self.next >= self.replay.actions.len() {
+            "finished"
+        } else if self.paused {
+            "paused"
+        } else if self.fast_forward {
+            "fast forward"
+        } else {
+            "playing"
+        };
+        format!(
+            "Replay {}/{} ({}) - {} pause, {} step, {} fast forward",
+            self.next,
+            self.replay.actions.len(),
+            mode,
+            keymap.keys_for(Action::ReplayPause),
+            keymap.keys_for(Action::ReplayStep),
+            keymap.keys_for(Action::ReplayFastForward)
+        )
+    }
+
+    // feeds recorded actions to the game in place of the keyboard
+    // the action is whatever the pressed key is bound to, only the replay controls are used
+    pub fn tick(&mut self, game: &mut Game, action: Option<Action>) {
+        let mut step = false;
+        match action {
+            Some(Action::ReplayPause) => self.paused = !self.paused,
+            Some(Action::ReplayStep) => step = true,
+            Some(Action::ReplayFastForward) => self.fast_forward = !self.fast_forward,
+            _ => {}
+        }
+
+        let ticks = if self.fast_forward {
+            FAST_FORWARD_TICKS
+        } else {
+            1
+        };
+        for _ in 0..ticks {
+            match game.turn_state() {
+                TurnState::GameOver | TurnState::Victory => break,
+                TurnState::AwaitingInput => {
+                    // a paused replay still finishes the turn it is in
+                    if self.paused && !step {
+                        break;
+                    }
+                    step = false;
+                    match self.next_action() {
+                        Some(action) => game.tick(Some(action)),
+                        None => {
+                            self.paused = true;
+                            break;
+                        }
+                    }
+                }
+                _ => game.tick(None),
+            }
+        }
+    }
+}
@@ -0,0 +1,292 @@
+use crate::prelude::*;
+use legion::world::EntryRef;
+use ron::ser::{PrettyConfig, to_string_pretty};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+
+// bump whenever the layout of SaveGame or SavedEntity changes
+pub const SAVE_VERSION: u32 = 10;
+pub const SAVE_FILE: &str = "savegame.ron";
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    Format(ron::Error),
+    IncompatibleVersion { found: u32, expected: u32 },
+    UnknownTheme(String),
+    MissingResource(&'static str),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(e) => write!(f, "Could not access save file: {}", e),
+            SaveError::Format(e) => write!(f, "Save file is corrupt: {}", e),
+            SaveError::IncompatibleVersion { found, expected } => write!(
+                f,
+                "Save file is version {} but this game reads version {}",
+                found, expected
+            ),
+            SaveError::UnknownTheme(name) => write!(f, "Save file uses unknown theme: {}", name),
+            SaveError::MissingResource(name) => write!(f, "Nothing to save, missing {}", name),
+        }
+    }
+}
+
+impl From<std::io::Error> for SaveError {
+    fn from(e: std::io::Error) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+impl From<ron::Error> for SaveError {
+    fn from(e: ron::Error) -> Self {
+        SaveError::Format(e)
+    }
+}
+
+// only the version is read first, so old files fail with a clear message
+// instead of a parse error on whichever field changed
+#[derive(Deserialize)]
+#[serde(rename = "SaveGame")]
+struct SaveHeader {
+    version: u32,
+}
+
+// every component that outlives a turn, as plain data
+// message components (WantsToMove etc.) are never saved
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SavedEntity {
+    pub render: Render,
+    pub position: Option<Point>,
+    pub player: Option<Player>,
+    pub name: Option<Name>,
+    pub health: Option<Health>,
+    pub fov_radius: Option<i32>,
+    pub damage: Option<Damage>,
+    pub healing: Option<ProvidesHealing>,
+    pub enemy: bool,
+    pub item: bool,
+    pub grail: bool,
+    pub weapon: bool,
+    pub trap: bool,
+    pub key: bool,
+    pub dungeon_map: bool,
+    pub brain: Option<Brain>,
+    pub last_seen: Option<LastSeenPlayer>,
+    pub energy: Option<Energy>,
+    pub carried: bool, // carried by the player
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SaveGame {
+    pub version: u32,
+    pub map: Map,
+    pub camera: Camera,
+    pub turn_state: TurnState,
+    pub theme: String,
+    pub seed: GameSeed,
+    pub ai_rng: RandomNumberGenerator, // reseeding it would send the monsters another way
+    pub levels: DungeonLevels,         // floors the player has left
+    pub entities: Vec<SavedEntity>,
+}
+
+fn has<T: legion::storage::Component>(entry: &EntryRef) -> bool {
+    entry.get_component::<T>().is_ok()
+}
+
+// also used to keep the monsters and items of floors the player has left
+pub fn snapshot_entity(entry: &EntryRef, render: Render) -> SavedEntity {
+    SavedEntity {
+        render,
+        position: entry.get_component::<Point>().ok().copied(),
+        player: entry.get_component::<Player>().ok().copied(),
+        name: entry.get_component::<Name>().ok().cloned(),
+        health: entry.get_component::<Health>().ok().copied(),
+        fov_radius: entry
+            .get_component::<FieldOfView>()
+            .ok()
+            .map(|fov| fov.radius),
+        damage: entry.get_component::<Damage>().ok().copied(),
+        healing: entry.get_component::<ProvidesHealing>().ok().copied(),
+        enemy: has::<Enemy>(entry),
+        item: has::<Item>(entry),
+        grail: has::<Grail>(entry),
+        weapon: has::<Weapon>(entry),
+        trap: has::<Trap>(entry),
+        key: has::<Key>(entry),
+        dungeon_map: has::<ProvidesDungeonMap>(entry),
+        brain: entry.get_component::<Brain>().ok().copied(),
+        last_seen: entry.get_component::<LastSeenPlayer>().ok().copied(),
+        energy: entry.get_component::<Energy>().ok().copied(),
+        carried: has::<Carried>(entry),
+    }
+}
+
+impl SaveGame {
+    // capture the world and resources needed to rebuild an identical game
+    pub fn capture(ecs: &World, resources: &Resources) -> Result<Self, SaveError> {
+        let map = resources
+            .get::<Map>()
+            .ok_or(SaveError::MissingResource("map"))?
+            .clone();
+        let camera = *resources
+            .get::<Camera>()
+            .ok_or(SaveError::MissingResource("camera"))?;
+        let turn_state = *resources
+            .get::<TurnState>()
+            .ok_or(SaveError::MissingResource("turn state"))?;
+        let theme = resources
+            .get::<Box<dyn MapTheme>>()
+            .ok_or(SaveError::MissingResource("theme"))?
+            .name()
+            .to_string();
+        let seed = *resources
+            .get::<GameSeed>()
+            .ok_or(SaveError::MissingResource("seed"))?;
+        let ai_rng = resources
+            .get::<RandomNumberGenerator>()
+            .ok_or(SaveError::MissingResource("ai rng"))?
+            .clone();
+        let levels = resources
+            .get::<DungeonLevels>()
+            .ok_or(SaveError::MissingResource("dungeon levels"))?
+            .clone();
+
+        // every persistent entity has a Render component, messages don't
+        let entities = <(Entity, &Render)>::query()
+            .iter(ecs)
+            .filter_map(|(entity, render)| {
+                ecs.entry_ref(*entity)
+                    .ok()
+                    .map(|entry| snapshot_entity(&entry, *render))
+            })
+            .collect();
+
+        Ok(Self {
+            version: SAVE_VERSION,
+            map,
+            camera,
+            turn_state,
+            theme,
+            seed,
+            ai_rng,
+            levels,
+            entities,
+        })
+    }
+
+    pub fn write(&self, path: &str) -> Result<(), SaveError> {
+        let contents = to_string_pretty(self, PrettyConfig::default())?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    pub fn read(path: &str) -> Result<Self, SaveError> {
+        let contents = fs::read_to_string(path)?;
+        let header: SaveHeader = ron::de::from_str(&contents)?;
+        if header.version != SAVE_VERSION {
+            return Err(SaveError::IncompatibleVersion {
+                found: header.version,
+                expected: SAVE_VERSION,
+            });
+        }
+        Ok(ron::de::from_str(&contents)?)
+    }
+
+    // replace the contents of ecs and resources with the saved game
+    pub fn restore(self, ecs: &mut World, resources: &mut Resources) -> Result<(), SaveError> {
+        // check everything that can fail before touching the running game
+        let theme = theme_by_name(&self.theme)
+            .ok_or_else(|| SaveError::UnknownTheme(self.theme.clone()))?;
+        let player_render = self
+            .entities
+            .iter()
+            .find(|e| e.player.is_some())
+            .map(|e| e.render)
+            .ok_or(SaveError::MissingResource("player"))?;
+
+        *ecs = World::default();
+        let player = ecs.push((player_render,));
+
+        for saved in self.entities.iter() {
+            // player was pushed first so carried items can point at it
+            let entity = if saved.player.is_some() {
+                player
+            } else {
+                ecs.push((saved.render,))
+            };
+            restore_entity(ecs, entity, saved, player);
+        }
+
+        resources.insert(self.map);
+        resources.insert(self.camera);
+        resources.insert(self.turn_state);
+        resources.insert(theme);
+        resources.insert(self.ai_rng);
+        resources.insert(self.seed);
+        resources.insert(self.levels);
+        Ok(())
+    }
+}
+
+// adds the saved components to an entity that already has its Render
+pub fn restore_entity(ecs: &mut World, entity: Entity, saved: &SavedEntity, player: Entity) {
+    let mut entry = ecs.entry(entity).unwrap();
+    if let Some(pos) = saved.position {
+        entry.add_component(pos);
+    }
+    if let Some(p) = saved.player {
+        entry.add_component(p);
+    }
+    if let Some(name) = &saved.name {
+        entry.add_component(name.clone());
+    }
+    if let Some(health) = saved.health {
+        entry.add_component(health);
+    }
+    if let Some(radius) = saved.fov_radius {
+        // visible tiles are recalculated by the fov system
+        entry.add_component(FieldOfView::new(radius));
+    }
+    if let Some(damage) = saved.damage {
+        entry.add_component(damage);
+    }
+    if let Some(healing) = saved.healing {
+        entry.add_component(healing);
+    }
+    if saved.enemy {
+        entry.add_component(Enemy);
+    }
+    if saved.item {
+        entry.add_component(Item);
+    }
+    if saved.grail {
+        entry.add_component(Grail);
+    }
+    if saved.weapon {
+        entry.add_component(Weapon);
+    }
+    if saved.trap {
+        entry.add_component(Trap);
+    }
+    if saved.key {
+        entry.add_component(Key);
+    }
+    if saved.dungeon_map {
+        entry.add_component(ProvidesDungeonMap);
+    }
+    if let Some(brain) = saved.brain {
+        entry.add_component(brain);
+    }
+    if let Some(last_seen) = saved.last_seen {
+        entry.add_component(last_seen);
+    }
+    if let Some(energy) = saved.energy {
+        entry.add_component(energy);
+    }
+    if saved.carried {
+        entry.add_component(Carried(player));
+    }
+}
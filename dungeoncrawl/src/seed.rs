@@ -0,0 +1,25 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// every random roll in a run derives from this, so the same seed replays the same run
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GameSeed(pub u64);
+
+impl GameSeed {
+    pub fn random() -> Self {
+        Self(RandomNumberGenerator::new().next_u64())
+    }
+
+    // each level gets its own generator, so a floor is the same no matter
+    // how many rolls the monsters used on the floors before it
+    pub fn level_rng(&self, level: u32) -> RandomNumberGenerator {
+        RandomNumberGenerator::seeded(
+            self.0 ^ (level as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15),
+        )
+    }
+
+    // generator for monster decisions, inserted as a resource for the AI systems
+    pub fn ai_rng(&self) -> RandomNumberGenerator {
+        RandomNumberGenerator::seeded(self.0)
+    }
+}
@@ -1,7 +1,8 @@
 mod template;
 
 use crate::prelude::*;
-use template::*;
+pub(crate) use template::open_resource;
+pub use template::{EntityType, Template, Templates};
 
 pub fn spawn_level(
     ecs: &mut World,
@@ -13,6 +14,12 @@ pub fn spawn_level(
     template.spawn_entities(ecs, rng, level, spawn_points);
 }
 
+pub fn spawn_named_entities(ecs: &mut World, spawns: &[(Point, String)]) {
+    if !spawns.is_empty() {
+        Templates::load().spawn_named(ecs, spawns);
+    }
+}
+
 pub fn spawn_player(ecs: &mut World, position: Point) {
     ecs.push((
         Player { map_level: 0 }, // tag component indicating entity is a player
@@ -27,6 +34,7 @@ pub fn spawn_player(ecs: &mut World, position: Point) {
         },
         FieldOfView::new(8),
         Damage(1),
+        Energy::new(NORMAL_SPEED),
     ));
 }
 
@@ -2,26 +2,35 @@ use crate::prelude::*;
 use legion::systems::CommandBuffer;
 use ron::de::from_reader;
 use serde::Deserialize;
-use std::collections::HashSet;
 use std::{fs::File, path::PathBuf};
 
 // every type in struct needs to support Deserialize
 #[derive(Deserialize, Clone, Debug)]
 pub struct Template {
     pub entity_type: EntityType,
-    pub levels: HashSet<usize>,
+    // first and last level the entity can spawn on, no max_level means every deeper level
+    #[serde(default)]
+    pub min_level: usize,
+    pub max_level: Option<usize>,
     pub frequency: i32,
     pub name: String,
     pub glyph: char,
     pub provides: Option<Vec<(String, i32)>>,
     pub hp: Option<i32>,
     pub base_damage: Option<i32>,
+    // how an enemy acts, see Behaviour
+    #[serde(default)]
+    pub behaviour: Behaviour,
+    // energy gained a round, NORMAL_SPEED acts once a round
+    #[serde(default = "default_speed")]
+    pub speed: i32,
 }
 
 #[derive(Deserialize, Clone, Debug, PartialEq)]
 pub enum EntityType {
     Enemy,
     Item,
+    Trap,
 }
 
 // top level collection representing file, vector of templates
@@ -30,19 +39,19 @@ pub struct Templates {
     pub entities: Vec<Template>,
 }
 
-fn open_resource(rel: &str) -> std::io::Result<File> {
+pub(crate) fn open_resource(rel: &str) -> std::io::Result<File> {
     // 1) Try current working dir (works if main already set CWD to the exe dir)
     if let Ok(f) = File::open(rel) {
         return Ok(f);
     }
 
     // 2) Try alongside the executable (works even if CWD wasn’t fixed)
-    if let Ok(exe) = std::env::current_exe() {
-        if let Some(dir) = exe.parent() {
-            let p = dir.join(rel);
-            if let Ok(f) = File::open(&p) {
-                return Ok(f);
-            }
+    if let Ok(exe) = std::env::current_exe()
+        && let Some(dir) = exe.parent()
+    {
+        let p = dir.join(rel);
+        if let Ok(f) = File::open(&p) {
+            return Ok(f);
         }
     }
 
@@ -77,7 +86,7 @@ impl Templates {
         self.entities
             .iter()
             // only consider entities that can appear on this level
-            .filter(|e| e.levels.contains(&level))
+            .filter(|e| e.min_level <= level && e.max_level.is_none_or(|max| level <= max))
             // add entities to available list according to frequency
             // e.g. frequency of 3 means 3 entries in list to increase chance of selection
             .for_each(|t| {
@@ -97,6 +106,18 @@ impl Templates {
         commands.flush(ecs);
     }
 
+    // entities a prefab asked for by name, whatever the level
+    pub fn spawn_named(&self, ecs: &mut World, spawns: &[(Point, String)]) {
+        let mut commands = CommandBuffer::new(ecs);
+        // Prefabs::check rejects names without a template when prefabs.ron is loaded
+        spawns.iter().for_each(|(pt, name)| {
+            if let Some(template) = self.entities.iter().find(|t| t.name == *name) {
+                self.spawn_entity(pt, template, &mut commands);
+            }
+        });
+        commands.flush(ecs);
+    }
+
     fn spawn_entity(
         &self,
         pt: &Point,
@@ -104,7 +125,7 @@ impl Templates {
         commands: &mut legion::systems::CommandBuffer,
     ) {
         let entity = commands.push((
-            pt.clone(),
+            *pt,
             Render {
                 color: ColorPair::new(WHITE, BLACK),
                 glyph: to_cp437(template.glyph),
@@ -114,10 +135,12 @@ impl Templates {
 
         match template.entity_type {
             EntityType::Item => commands.add_component(entity, Item {}),
+            EntityType::Trap => commands.add_component(entity, Trap),
             EntityType::Enemy => {
                 commands.add_component(entity, Enemy {});
                 commands.add_component(entity, FieldOfView::new(6));
-                commands.add_component(entity, ChasingPlayer {});
+                commands.add_component(entity, Brain::new(template.behaviour, *pt));
+                commands.add_component(entity, Energy::new(template.speed));
                 commands.add_component(
                     entity,
                     Health {
@@ -138,6 +161,9 @@ impl Templates {
                     "MagicMap" => {
                         commands.add_component(entity, ProvidesDungeonMap {});
                     }
+                    "Key" => {
+                        commands.add_component(entity, Key);
+                    }
                     _ => {
                         println!("Unknown effect type: {}", provides);
                     }
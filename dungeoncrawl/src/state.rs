@@ -0,0 +1,280 @@
+use crate::prelude::*;
+
+// shown in place of the map until closed, the dungeon waits meanwhile
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Screen {
+    Help,
+    Inventory,
+}
+
+// bracket-lib front end: turns key presses into actions, draws the game and the end screens
+pub struct State {
+    // Game state fields go here
+    game: Game,
+    render_systems: Schedule,
+    load_error: Option<String>, // shown on the end screens if loading fails
+    replay: Option<ReplayPlayer>, // watching a replay instead of playing
+    keymap: Keymap,
+    screen: Option<Screen>,
+    debug_systems: Schedule,
+    show_ai: bool, // the monster ai overlay
+}
+
+impl State {
+    pub fn new(fixed_seed: Option<GameSeed>, campaign: Campaign, keymap: Keymap) -> Self {
+        Self {
+            game: Game::with_campaign(fixed_seed, campaign),
+            render_systems: build_render_scheduler(),
+            load_error: None,
+            replay: None,
+            keymap,
+            screen: None,
+            debug_systems: build_debug_scheduler(),
+            show_ai: false,
+        }
+    }
+
+    pub fn watch(replay: Replay, mut campaign: Campaign, keymap: Keymap) -> Self {
+        replay.apply_to(&mut campaign);
+        let player = ReplayPlayer::new(replay);
+        let mut game = Game::with_campaign(Some(player.seed()), campaign);
+        // don't overwrite the replay being watched
+        game.recording = None;
+        Self {
+            game,
+            render_systems: build_render_scheduler(),
+            load_error: None,
+            replay: Some(player),
+            keymap,
+            screen: None,
+            debug_systems: build_debug_scheduler(),
+            show_ai: false,
+        }
+    }
+
+    fn reset_game_state(&mut self) {
+        self.load_error = None;
+        self.replay = None;
+        self.screen = None;
+        self.game.reset_game_state();
+    }
+
+    fn toggle_screen(&mut self, screen: Screen) {
+        self.screen = if self.screen == Some(screen) {
+            None
+        } else {
+            Some(screen)
+        };
+    }
+
+    // the action for a key press, the help and inventory screens take the keys while open
+    fn input(&mut self, key: Option<VirtualKeyCode>) -> Option<Action> {
+        let action = key.and_then(|key| self.keymap.action(key));
+        match action {
+            Some(Action::Help) => self.toggle_screen(Screen::Help),
+            Some(Action::Inventory) => self.toggle_screen(Screen::Inventory),
+            Some(Action::AiDebug) => self.show_ai = !self.show_ai,
+            _ if self.screen.is_none() => return action,
+            // using an item from the inventory closes it
+            Some(Action::Use(_)) if self.screen == Some(Screen::Inventory) => {
+                self.screen = None;
+                return action;
+            }
+            _ if key == Some(VirtualKeyCode::Escape) => self.screen = None,
+            _ => {}
+        }
+        None
+    }
+
+    // drawn instead of the map, like the end screens
+    fn draw_screen(&self, ctx: &mut BTerm, screen: Screen) {
+        let (title, lines, close) = match screen {
+            Screen::Help => ("Keys", self.keymap.help_lines(), Action::Help),
+            Screen::Inventory => ("Inventory", self.inventory_lines(), Action::Inventory),
+        };
+        ctx.set_active_console(2); // use top layer for UI
+        ctx.print_color_centered(6, YELLOW, BLACK, title);
+        let left = SCREEN_WIDTH - 25;
+        for (y, line) in lines.iter().enumerate() {
+            ctx.print(left, 9 + y as i32, line);
+        }
+        ctx.print_color(
+            left,
+            11 + lines.len() as i32,
+            GRAY,
+            BLACK,
+            format!("{} or Escape to close", self.keymap.keys_for(close)),
+        );
+    }
+
+    // carried items next to the keys that use them
+    fn inventory_lines(&self) -> Vec<String> {
+        let ecs = &self.game.ecs;
+        let player = <Entity>::query()
+            .filter(component::<Player>())
+            .iter(ecs)
+            .next()
+            .copied();
+        let lines: Vec<String> = <(&Item, &Name, &Carried)>::query()
+            .iter(ecs)
+            .filter(|(_, _, carried)| Some(carried.0) == player)
+            .enumerate()
+            .map(|(i, (_, name, _))| {
+                format!("{:<12}{}", self.keymap.keys_for(Action::Use(i)), name.0)
+            })
+            .collect();
+        if lines.is_empty() {
+            vec!["You aren't carrying anything.".to_string()]
+        } else {
+            lines
+        }
+    }
+
+    // what the end screens offer, with the keys from the keymap
+    fn end_screen_input(&mut self, ctx: &mut BTerm) {
+        ctx.print_color_centered(
+            18,
+            GREEN,
+            BLACK,
+            format!(
+                "Press {} to play again.",
+                self.keymap.keys_for(Action::Restart)
+            ),
+        );
+        ctx.print_color_centered(
+            20,
+            GREEN,
+            BLACK,
+            format!(
+                "Press {} to load your last save.",
+                self.keymap.keys_for(Action::Load)
+            ),
+        );
+        ctx.print_color_centered(24, WHITE, BLACK, self.seed_text());
+        if let Some(error) = &self.load_error {
+            ctx.print_color_centered(22, RED, BLACK, error);
+        }
+        if let Some(message) = self.game.resources.get::<MessageLog>().unwrap().latest() {
+            ctx.print_color_centered(26, GRAY, BLACK, message);
+        }
+
+        match ctx.key.and_then(|key| self.keymap.action(key)) {
+            Some(Action::Restart) => self.reset_game_state(),
+            Some(Action::Load) => self.load_game(),
+            _ => {}
+        }
+    }
+
+    fn load_game(&mut self) {
+        match self.game.load_game() {
+            Ok(()) => self.load_error = None,
+            Err(e) => self.load_error = Some(format!("Load failed: {}", e)),
+        }
+    }
+
+    // seed line for the end screens, so a run can be reported and replayed
+    fn seed_text(&self) -> String {
+        format!("Seed: {}", self.game.seed().0)
+    }
+
+    fn game_over(&mut self, ctx: &mut BTerm) {
+        ctx.cls();
+        ctx.set_active_console(2); // use top layer for UI
+        ctx.print_color_centered(10, RED, BLACK, "Your journey has ended.");
+        ctx.print_color_centered(
+            12,
+            WHITE,
+            BLACK,
+            "Slain by an enemy, your adventure is over.",
+        );
+        ctx.print_color_centered(
+            14,
+            WHITE,
+            BLACK,
+            "The grail remains unclaimed, and your home town is lost.",
+        );
+        ctx.print_color_centered(16, WHITE, BLACK, "Don't worry, you can always try again.");
+        self.end_screen_input(ctx);
+    }
+
+    fn victory(&mut self, ctx: &mut BTerm) {
+        ctx.cls();
+        ctx.set_active_console(2); // use top layer for UI
+        ctx.print_color_centered(10, GOLD, BLACK, "You found the Holy Grail!");
+        ctx.print_color_centered(
+            12,
+            WHITE,
+            BLACK,
+            "With the grail in hand, you return to your home town.",
+        );
+        ctx.print_color_centered(
+            14,
+            WHITE,
+            BLACK,
+            "The townsfolk rejoice as you bring them salvation.",
+        );
+        ctx.print_color_centered(16, WHITE, BLACK, "Congratulations on your victory!");
+        self.end_screen_input(ctx);
+    }
+}
+
+impl GameState for State {
+    fn tick(&mut self, ctx: &mut BTerm) {
+        // multiple layers
+        ctx.set_active_console(0);
+        ctx.cls();
+        ctx.set_active_console(1);
+        ctx.cls();
+        ctx.set_active_console(2);
+        ctx.cls();
+        ctx.set_active_console(0); // get mouse pos coordinates from correct layer
+        // tuple of x,y coordinates, used by the tooltips
+        self.game
+            .resources
+            .insert(Point::from_tuple(ctx.mouse_pos()));
+        match self.game.turn_state() {
+            TurnState::GameOver => self.game_over(ctx),
+            TurnState::Victory => self.victory(ctx),
+            _ => {
+                if let Some(replay) = &mut self.replay {
+                    // the overlay helps most when watching a run back
+                    let action = ctx.key.and_then(|key| self.keymap.action(key));
+                    if action == Some(Action::AiDebug) {
+                        self.show_ai = !self.show_ai;
+                    }
+                    replay.tick(&mut self.game, action);
+                } else {
+                    // turn the current key press into an action for the rules systems
+                    let action = self.input(ctx.key);
+                    self.game.tick(action);
+                }
+                // the hud shows the help key, starting again or loading clears the resources
+                if !self.game.resources.contains::<Keymap>() {
+                    self.game.resources.insert(self.keymap.clone());
+                }
+                match self.screen {
+                    Some(screen) => self.draw_screen(ctx, screen),
+                    None => {
+                        self.render_systems
+                            .execute(&mut self.game.ecs, &mut self.game.resources);
+                        if self.show_ai {
+                            self.debug_systems
+                                .execute(&mut self.game.ecs, &mut self.game.resources);
+                        }
+                    }
+                }
+                if let Some(replay) = &self.replay {
+                    ctx.set_active_console(2);
+                    ctx.print_color(
+                        1,
+                        SCREEN_HEIGHT * 2 - 2,
+                        YELLOW,
+                        BLACK,
+                        replay.status(&self.keymap),
+                    );
+                }
+            }
+        }
+        render_draw_buffer(ctx).expect("Render error");
+    }
+}
@@ -0,0 +1,85 @@
+use crate::prelude::*;
+
+#[system]
+#[read_component(Point)]
+#[read_component(Player)]
+#[read_component(FieldOfView)]
+#[read_component(Health)]
+#[read_component(Recovering)]
+#[write_component(Brain)]
+#[write_component(LastSeenPlayer)]
+pub fn ai(
+    ecs: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] map: &Map,
+    #[resource] rng: &mut RandomNumberGenerator,
+) {
+    let player_pos = <&Point>::query()
+        .filter(component::<Player>())
+        .iter(ecs)
+        .next()
+        .copied();
+
+    // only monsters acting on this pass think
+    let mut brains = <(
+        Entity,
+        &Point,
+        &FieldOfView,
+        Option<&Health>,
+        Option<&mut LastSeenPlayer>,
+        &mut Brain,
+    )>::query()
+    .filter(!component::<Recovering>());
+    brains
+        .iter_mut(ecs)
+        .for_each(|(entity, pos, fov, health, memory, brain)| {
+            // monsters only know what their own field of view shows them
+            let seen = player_pos.filter(|p| fov.visible_tiles.contains(p));
+            let search_turns = brain.behaviour.search_turns;
+
+            // seeing the player refreshes the memory, searching near it uses it up
+            let last_seen = match (seen, memory) {
+                (Some(player), Some(memory)) => {
+                    memory.position = player;
+                    memory.turns_left = search_turns;
+                    None
+                }
+                (Some(player), None) => {
+                    commands.add_component(
+                        *entity,
+                        LastSeenPlayer {
+                            position: player,
+                            turns_left: search_turns,
+                        },
+                    );
+                    None
+                }
+                (None, Some(memory)) => {
+                    let near = DistanceAlg::Pythagoras.distance2d(*pos, memory.position)
+                        <= SEARCH_RADIUS as f32;
+                    if brain.state == AiState::Search && near {
+                        memory.turns_left -= 1;
+                    }
+                    if memory.turns_left > 0 {
+                        Some(memory.position)
+                    } else {
+                        commands.remove_component::<LastSeenPlayer>(*entity);
+                        None
+                    }
+                }
+                (None, None) => None,
+            };
+            brain.think(seen, last_seen, health);
+            // anything that stopped hunting forgets
+            if last_seen.is_some() && brain.state != AiState::Search {
+                commands.remove_component::<LastSeenPlayer>(*entity);
+            }
+
+            // a patrol picks somewhere new once it gets where it was going
+            let arrived = matches!(brain.state, AiState::Patrol(target) if target == *pos);
+            if arrived {
+                let next = brain.next_patrol_point(map, rng).unwrap_or(brain.home);
+                brain.state = AiState::Patrol(next);
+            }
+        });
+}
@@ -0,0 +1,31 @@
+use crate::prelude::*;
+
+// debug overlay: what every monster is doing and where it remembers the player
+#[system]
+#[read_component(Point)]
+#[read_component(Brain)]
+#[read_component(LastSeenPlayer)]
+pub fn ai_debug(ecs: &SubWorld, #[resource] camera: &Camera) {
+    let offset = Point::new(camera.left_x, camera.top_y);
+    let mut draw_batch = DrawBatch::new();
+    draw_batch.target(2);
+
+    <(&Point, &Brain, Option<&LastSeenPlayer>)>::query()
+        .iter(ecs)
+        .for_each(|(pos, brain, memory)| {
+            // text layer is 4x larger, like the tooltips
+            draw_batch.print_color(
+                (*pos - offset) * 4 + Point::new(0, -1),
+                brain.state.name(),
+                ColorPair::new(CYAN, BLACK),
+            );
+            if let Some(memory) = memory {
+                draw_batch.print_color(
+                    (memory.position - offset) * 4,
+                    format!("?{}", memory.turns_left),
+                    ColorPair::new(YELLOW, BLACK),
+                );
+            }
+        });
+    draw_batch.submit(10200).expect("Batch error");
+}
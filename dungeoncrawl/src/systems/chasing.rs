@@ -2,72 +2,58 @@ use crate::prelude::*;
 
 #[system]
 #[read_component(Point)]
-#[read_component(ChasingPlayer)]
-#[read_component(Health)]
+#[read_component(Brain)]
+#[read_component(Recovering)]
 #[read_component(Player)]
-#[read_component(FieldOfView)]
 
-pub fn chasing(#[resource] map: &Map, ecs: &SubWorld, commands: &mut CommandBuffer) {
-    let mut movers = <(Entity, &Point, &ChasingPlayer, &FieldOfView)>::query();
-    let mut positions = <(Entity, &Point, &Health)>::query();
-    let mut player = <(&Point, &Player)>::query();
-    let player_pos = player.iter(ecs).nth(0).unwrap().0;
-    let player_idx = map_idx(player_pos.x, player_pos.y);
-    let search_targets = vec![player_idx];
-    let dijkstra_map = DijkstraMap::new(
-        SCREEN_WIDTH, // not derived from map to allow for smaller search if needed
-        SCREEN_HEIGHT,
-        &search_targets,
-        map,
-        1024.0, // max search distance
-    );
+pub fn chasing(
+    #[resource] map: &Map,
+    #[resource] flow_fields: &mut FlowFields,
+    #[resource] occupancy: &mut Occupancy,
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+) {
+    let mut movers = <(Entity, &Point, &Brain)>::query().filter(!component::<Recovering>());
+    let mut players = <(Entity, &Point)>::query().filter(component::<Player>());
+    let (player, player_pos) = players.iter(ecs).next().unwrap();
+    // shared with the other ai systems, only rebuilt once the player has moved
+    let dijkstra_map = flow_fields.toward(Goal::Player, &[*player_pos], map);
 
-    movers.iter(ecs).for_each(|(entity, pos, _, fov)| {
-        // only chase if the player is in the entity's FOV
-        if !fov.visible_tiles.contains(player_pos) {
+    movers.iter(ecs).for_each(|(entity, pos, brain)| {
+        // the ai system only starts a chase once the player is in sight
+        if brain.state != AiState::Chase {
             return;
         }
-        let idx = map_idx(pos.x, pos.y);
-        // returns Option, use if let to only continue if Some
-        if let Some(destination) = DijkstraMap::find_lowest_exit(&dijkstra_map, idx, map) {
-            let distance = DistanceAlg::Pythagoras.distance2d(*pos, *player_pos);
-            // only move if further than 1.2 away (to avoid diagonal moves when adjacent)
-            // 1.2 is > 1.0 (adjacent) but < 1.4 (diagonal)
-            let destination = if distance > 1.2 {
-                map.index_to_point2d(destination)
-            } else {
-                *player_pos // don't move if already adjacent to player
-            };
-            let mut attacked = false;
-            positions
-                .iter(ecs)
-                .filter(|(_, target_pos, _)| **target_pos == destination)
-                .for_each(|(victim, _, _)| {
-                    if ecs
-                        .entry_ref(*victim)
-                        .unwrap()
-                        .get_component::<Player>()
-                        .is_ok()
-                    {
-                        commands.push((
-                            (),
-                            WantsToAttack {
-                                attacker: *entity,
-                                victim: *victim,
-                            },
-                        ));
-                    }
-                    attacked = true;
-                });
-            if !attacked {
-                commands.push((
-                    (),
-                    WantsToMove {
-                        entity: *entity,
-                        destination,
-                    },
-                ));
-            }
+        // next to the player by the map's rules, diagonals only count when they're turned on
+        if map.is_step(*pos, *player_pos) {
+            commands.push((
+                (),
+                WantsToAttack {
+                    attacker: *entity,
+                    victim: *player,
+                },
+            ));
+            return;
+        }
+        // downhill towards the player, stepping round anyone already there or on their way
+        let idx = map.point2d_to_index(*pos);
+        let next = map
+            .get_available_exits(idx)
+            .iter()
+            .map(|(exit, _)| *exit)
+            .filter(|exit| dijkstra_map.map[*exit] < dijkstra_map.map[idx])
+            .filter(|exit| occupancy.is_free(map.index_to_point2d(*exit)))
+            .min_by(|a, b| dijkstra_map.map[*a].total_cmp(&dijkstra_map.map[*b]))
+            .map(|exit| map.index_to_point2d(exit));
+        if let Some(destination) = next {
+            occupancy.reserve(*entity, destination);
+            commands.push((
+                (),
+                WantsToMove {
+                    entity: *entity,
+                    destination,
+                },
+            ));
         }
     });
 }
@@ -6,7 +6,9 @@ use crate::prelude::*;
 #[write_component(Health)]
 #[read_component(Damage)]
 #[read_component(Carried)]
-pub fn combat(ecs: &mut SubWorld, commands: &mut CommandBuffer) {
+#[read_component(Point)]
+#[write_component(Energy)]
+pub fn combat(ecs: &mut SubWorld, commands: &mut CommandBuffer, #[resource] map: &Map) {
     let mut attackers = <(Entity, &WantsToAttack)>::query();
 
     // taking the iterator of attackers and collecting into a vector
@@ -17,6 +19,21 @@ pub fn combat(ecs: &mut SubWorld, commands: &mut CommandBuffer) {
         .collect();
 
     victims.iter().for_each(|(message, attacker, victim)| {
+        // melee only reaches a tile you could step to, the same rule movement uses
+        let position = |entity: &Entity| {
+            ecs.entry_ref(*entity)
+                .ok()
+                .and_then(|e| e.get_component::<Point>().ok().copied())
+        };
+        let out_of_reach = matches!(
+            (position(attacker), position(victim)),
+            (Some(from), Some(to)) if !map.is_step(from, to)
+        );
+        if out_of_reach {
+            commands.remove(*message);
+            return;
+        }
+
         // check if the victim is a player (to prevent removing the player on death)
         let is_player = ecs
             .entry_ref(*victim)
@@ -42,6 +59,15 @@ pub fn combat(ecs: &mut SubWorld, commands: &mut CommandBuffer) {
 
         let final_damage = base_damage + weapon_damage;
 
+        // the turn is already paid for, a heavy attack costs its attacker extra on top
+        if let Ok(energy) = ecs
+            .entry_mut(*attacker)
+            .unwrap()
+            .get_component_mut::<Energy>()
+        {
+            energy.spend(attack_cost(final_damage) - ACTION_COST);
+        }
+
         if let Ok(health) = ecs
             .entry_mut(*victim)
             .unwrap()
@@ -0,0 +1,70 @@
+use crate::prelude::*;
+
+#[system]
+#[read_component(WantsToOpenDoor)]
+#[read_component(WantsToCloseDoor)]
+#[read_component(Carried)]
+#[read_component(Key)]
+#[write_component(FieldOfView)]
+pub fn doors(
+    ecs: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] map: &mut Map,
+    #[resource] log: &mut MessageLog,
+) {
+    let mut changed = false;
+
+    // collect first, do not modify ecs while iterating over it
+    let opening: Vec<(Entity, Entity, Point)> = <(Entity, &WantsToOpenDoor)>::query()
+        .iter(ecs)
+        .map(|(message, open)| (*message, open.entity, open.position))
+        .collect();
+    opening.iter().for_each(|(message, opener, position)| {
+        let idx = map.point2d_to_index(*position);
+        match map.tiles[idx] {
+            TileType::Door => {
+                map.tiles[idx] = TileType::OpenDoor;
+                changed = true;
+            }
+            TileType::LockedDoor => {
+                let key = <(Entity, &Carried)>::query()
+                    .filter(component::<Key>())
+                    .iter(ecs)
+                    .find(|(_, carried)| carried.0 == *opener)
+                    .map(|(key, _)| *key);
+                match key {
+                    Some(key) => {
+                        // the key stays in the lock
+                        commands.remove(key);
+                        map.tiles[idx] = TileType::OpenDoor;
+                        changed = true;
+                    }
+                    None => log.add("The door is locked."),
+                }
+            }
+            _ => {}
+        }
+        commands.remove(*message);
+    });
+
+    let closing: Vec<(Entity, Point)> = <(Entity, &WantsToCloseDoor)>::query()
+        .iter(ecs)
+        .map(|(message, close)| (*message, close.position))
+        .collect();
+    closing.iter().for_each(|(message, position)| {
+        let idx = map.point2d_to_index(*position);
+        // an unlocked door stays unlocked once closed
+        if map.tiles[idx] == TileType::OpenDoor {
+            map.tiles[idx] = TileType::Door;
+            changed = true;
+        }
+        commands.remove(*message);
+    });
+
+    // a door changes what everyone can see, not just whoever used it
+    if changed {
+        <&mut FieldOfView>::query()
+            .iter_mut(ecs)
+            .for_each(|fov| fov.is_dirty = true);
+    }
+}
@@ -5,20 +5,39 @@ use crate::prelude::*;
 #[read_component(Player)]
 #[read_component(Grail)]
 #[read_component(Point)]
-pub fn end_turn(ecs: &SubWorld, #[resource] turn_state: &mut TurnState, #[resource] map: &Map) {
+#[write_component(Energy)]
+pub fn end_turn(ecs: &mut SubWorld, #[resource] turn_state: &mut TurnState) {
     // Access the ECS world to query entities, filtering to player health
     let mut player_hp = <(&Health, &Point)>::query().filter(component::<Player>());
     let mut grail = <&Point>::query().filter(component::<Grail>());
-    let current_state = turn_state.clone();
-    // cannot do oneliner because of borrow checker
-    let grail_default = Point::new(-1, -1);
-    let grail_position = grail.iter(ecs).nth(0).unwrap_or(&grail_default);
-    // let grail_position = grail.iter(ecs).nth(0).unwrap();
+    let current_state = *turn_state;
+    // copied out so energy can be written below
+    let grail_position = grail
+        .iter(ecs)
+        .next()
+        .copied()
+        .unwrap_or(Point::new(-1, -1));
 
     let mut new_state = match current_state {
         TurnState::AwaitingInput => return,
         TurnState::PlayerTurn => TurnState::EnemyTurn,
-        TurnState::EnemyTurn => TurnState::AwaitingInput,
+        // monsters with energy to spare get another pass before the round ends
+        TurnState::EnemyTurn if !round_is_over(ecs) => TurnState::EnemyTurn,
+        TurnState::EnemyTurn => {
+            <&mut Energy>::query()
+                .iter_mut(ecs)
+                .for_each(|energy| energy.gain());
+            // a player still paying off a heavy attack sits the next round out
+            let player_ready = <&Energy>::query()
+                .filter(component::<Player>())
+                .iter(ecs)
+                .all(|energy| energy.is_ready());
+            if player_ready {
+                TurnState::AwaitingInput
+            } else {
+                TurnState::EnemyTurn
+            }
+        }
         _ => current_state,
     };
     player_hp.iter(ecs).for_each(|(hp, pos)| {
@@ -27,15 +46,9 @@ pub fn end_turn(ecs: &SubWorld, #[resource] turn_state: &mut TurnState, #[resour
             new_state = TurnState::GameOver;
         }
         // Check if the player has reached the grail
-        if pos == grail_position {
+        if *pos == grail_position {
             new_state = TurnState::Victory;
         }
-
-        // Check if player is on exit tiles
-        let idx = map.point2d_to_index(*pos);
-        if map.tiles[idx] == TileType::Exit {
-            new_state = TurnState::NextLevel;
-        }
     });
 
     *turn_state = new_state; // dereference to assign the new state
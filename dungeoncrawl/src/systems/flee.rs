@@ -0,0 +1,68 @@
+use crate::prelude::*;
+
+#[system]
+#[read_component(Point)]
+#[read_component(Brain)]
+#[read_component(Recovering)]
+#[read_component(Player)]
+pub fn flee(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] map: &Map,
+    #[resource] flow_fields: &mut FlowFields,
+    #[resource] occupancy: &mut Occupancy,
+) {
+    let fleeing: Vec<(Entity, Point)> = <(Entity, &Point, &Brain)>::query()
+        .filter(!component::<Recovering>())
+        .iter(ecs)
+        .filter(|(_, _, brain)| brain.state == AiState::Flee)
+        .map(|(entity, pos, _)| (*entity, *pos))
+        .collect();
+    // no one to run, no field to look at
+    if fleeing.is_empty() {
+        return;
+    }
+
+    let (player, player_pos) = <(Entity, &Point)>::query()
+        .filter(component::<Player>())
+        .iter(ecs)
+        .map(|(entity, pos)| (*entity, *pos))
+        .next()
+        .unwrap();
+    let safety = flow_fields.safety(player_pos, map);
+    fleeing.iter().for_each(|(entity, pos)| {
+        let idx = map.point2d_to_index(*pos);
+        // downhill on the safety field, as long as that is closer to safety than here
+        let away = map
+            .get_available_exits(idx)
+            .iter()
+            .map(|(exit, _)| *exit)
+            .filter(|exit| safety.map[*exit] < safety.map[idx])
+            .filter(|exit| occupancy.is_free(map.index_to_point2d(*exit)))
+            .min_by(|a, b| safety.map[*a].total_cmp(&safety.map[*b]))
+            .map(|exit| map.index_to_point2d(exit));
+        match away {
+            Some(destination) => {
+                occupancy.reserve(*entity, destination);
+                commands.push((
+                    (),
+                    WantsToMove {
+                        entity: *entity,
+                        destination,
+                    },
+                ));
+            }
+            // cornered, it fights back
+            None if map.is_step(*pos, player_pos) => {
+                commands.push((
+                    (),
+                    WantsToAttack {
+                        attacker: *entity,
+                        victim: player,
+                    },
+                ));
+            }
+            None => {}
+        }
+    });
+}
@@ -0,0 +1,29 @@
+use crate::prelude::*;
+
+// runs once a round, at the end of the monsters' turn, so standing in lava hurts everyone alike
+#[system]
+#[read_component(Point)]
+#[read_component(Player)]
+#[read_component(Energy)]
+#[write_component(Health)]
+pub fn hazards(ecs: &mut SubWorld, commands: &mut CommandBuffer, #[resource] map: &Map) {
+    // fast monsters get more than one pass a round, only the last counts
+    if !round_is_over(ecs) {
+        return;
+    }
+    <(Entity, &Point, &mut Health, Option<&Player>)>::query()
+        .iter_mut(ecs)
+        .for_each(|(entity, pos, health, player)| {
+            let Some(idx) = map.try_idx(*pos) else {
+                return;
+            };
+            let damage = map.tiles[idx].hazard_damage();
+            if damage > 0 {
+                health.current -= damage;
+                // the player is never removed, end_turn notices the loss
+                if health.current < 1 && player.is_none() {
+                    commands.remove(*entity);
+                }
+            }
+        });
+}
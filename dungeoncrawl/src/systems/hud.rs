@@ -6,14 +6,25 @@ use crate::prelude::*;
 #[read_component(Item)]
 #[read_component(Carried)]
 #[read_component(Name)]
-pub fn hud(ecs: &SubWorld) {
+pub fn hud(
+    ecs: &SubWorld,
+    #[resource] seed: &GameSeed,
+    #[resource] keymap: &Keymap,
+    #[resource] log: &MessageLog,
+) {
     let mut health_query = <&Health>::query().filter(component::<Player>());
     // get single entry for player health
-    let player_health = health_query.iter(ecs).nth(0).unwrap();
+    let player_health = health_query.iter(ecs).next().unwrap();
 
     let mut draw_batch = DrawBatch::new();
     draw_batch.target(2);
-    draw_batch.print_centered(1, "Explore the Dungeon. Cursor keys/WASD to move.");
+    draw_batch.print_centered(
+        1,
+        format!(
+            "Explore the Dungeon. {} for help.",
+            keymap.keys_for(Action::Help)
+        ),
+    );
     draw_batch.bar_horizontal(
         Point::zero(),
         SCREEN_WIDTH * 2,
@@ -29,7 +40,8 @@ pub fn hud(ecs: &SubWorld) {
     // display level
     let (_, map_level) = <(Entity, &Player)>::query()
         .iter(ecs)
-        .find_map(|(entity, player)| Some((*entity, player.map_level)))
+        .map(|(entity, player)| (*entity, player.map_level))
+        .next()
         .unwrap();
 
     // right justify
@@ -38,11 +50,17 @@ pub fn hud(ecs: &SubWorld) {
         format!("Dungeon Level: {}", map_level + 1),
         ColorPair::new(WHITE, BLACK),
     );
+    draw_batch.print_color_right(
+        Point::new(SCREEN_WIDTH * 2, 2),
+        format!("Seed: {}", seed.0),
+        ColorPair::new(GRAY, BLACK),
+    );
 
     // list carried items
     let player = <(Entity, &Player)>::query()
         .iter(ecs)
-        .find_map(|(entity, _player)| Some(*entity))
+        .map(|(entity, _player)| *entity)
+        .next()
         .unwrap();
     let mut item_query = <(&Item, &Name, &Carried)>::query();
     let mut y = 3;
@@ -62,5 +80,12 @@ pub fn hud(ecs: &SubWorld) {
         );
     }
 
+    // newest message at the bottom, clear of the replay status line
+    let messages = log.messages();
+    for (i, message) in messages.iter().enumerate() {
+        let y = SCREEN_HEIGHT * 2 - 3 - (messages.len() - 1 - i) as i32;
+        draw_batch.print(Point::new(1, y), message);
+    }
+
     draw_batch.submit(10000).expect("Batch error");
 }
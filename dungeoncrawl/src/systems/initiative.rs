@@ -0,0 +1,26 @@
+use crate::prelude::*;
+
+// takes the cost of a turn from whoever acts on this pass, combat charges heavy attacks the rest
+// monsters short of energy sit the pass out, anything without Energy acts every pass
+#[system]
+#[read_component(Player)]
+#[write_component(Energy)]
+pub fn initiative(
+    ecs: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] turn_state: &TurnState,
+) {
+    <(Entity, &mut Energy, Option<&Player>)>::query()
+        .iter_mut(ecs)
+        .for_each(
+            |(entity, energy, player)| match (*turn_state, player.is_some()) {
+                (TurnState::PlayerTurn, true) => energy.spend(ACTION_COST),
+                (TurnState::EnemyTurn, false) if energy.is_ready() => {
+                    energy.spend(ACTION_COST);
+                    commands.remove_component::<Recovering>(*entity);
+                }
+                (TurnState::EnemyTurn, false) => commands.add_component(*entity, Recovering),
+                _ => {}
+            },
+        );
+}
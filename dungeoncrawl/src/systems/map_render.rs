@@ -4,6 +4,8 @@ use crate::prelude::*;
 #[system]
 #[read_component(FieldOfView)]
 #[read_component(Player)]
+// legion looks resources up by their exact type, so the theme stays a &Box
+#[allow(clippy::borrowed_box)]
 pub fn map_render(
     ecs: &SubWorld,
     #[resource] map: &Map,
@@ -12,7 +14,7 @@ pub fn map_render(
 ) {
     // query for player fov
     let mut fov = <&FieldOfView>::query().filter(component::<Player>());
-    let player_fov = fov.iter(ecs).nth(0).unwrap();
+    let player_fov = fov.iter(ecs).next().unwrap();
     // append draw commands to the draw batch
     let mut draw_batch = DrawBatch::new();
     draw_batch.target(0);
@@ -23,7 +25,7 @@ pub fn map_render(
                 let pt = Point::new(x, y);
                 let offset = Point::new(camera.left_x, camera.top_y);
                 // only draw tiles that are in the player's FOV
-                let idx = map_idx(x, y);
+                let idx = map.idx(x, y);
                 // draw if in bounds and either visible or revealed
                 if map.in_bounds(pt)
                     && player_fov.visible_tiles.contains(&pt) | map.revealed_tiles[idx]
@@ -1,60 +1,96 @@
+mod ai;
+mod ai_debug;
 mod chasing;
 mod combat;
+mod doors;
 mod end_turn;
 mod entity_render;
+mod flee;
 mod fov;
+mod hazards;
 mod hud;
+mod initiative;
 mod map_render;
 mod movement;
+mod occupancy;
+mod patrol;
 mod player_input;
 mod random_move;
+mod search;
 mod tooltips;
+mod traps;
 mod use_item;
 use crate::prelude::*;
 
+// the input, player and enemy schedules only hold the rules of the game
+// so they can run without a window, drawing happens in the render schedule
 pub fn build_input_scheduler() -> Schedule {
     Schedule::builder()
         .add_system(player_input::player_input_system())
         .add_system(fov::fov_system())
-        .flush() // ensures deleted entities are removed before rendering
-        .add_system(map_render::map_render_system())
-        .add_system(entity_render::entity_render_system())
-        .add_system(hud::hud_system())
-        .add_system(tooltips::tooltips_system())
         .build()
 }
 
 pub fn build_player_scheduler() -> Schedule {
     Schedule::builder()
+        .add_system(initiative::initiative_system())
         .add_system(use_item::use_item_system())
         .add_system(combat::combat_system())
+        .add_system(doors::doors_system())
         .flush()
+        .add_system(occupancy::occupancy_system())
         .add_system(movement::movement_system())
-        .flush() // ensures deleted entities are removed before rendering
+        .flush()
+        .add_system(traps::traps_system())
+        .flush()
         .add_system(fov::fov_system())
         .flush()
-        .add_system(map_render::map_render_system())
-        .add_system(entity_render::entity_render_system())
-        .add_system(hud::hud_system())
         .add_system(end_turn::end_turn_system())
         .build()
 }
 
 pub fn build_enemy_scheduler() -> Schedule {
     Schedule::builder()
+        // runs once per pass, fast monsters get more than one pass a round
+        .add_system(initiative::initiative_system())
+        .flush()
+        // ai picks each monster's state, the systems after it act on one state each
+        // and claim the tiles they step onto so monsters go round each other
+        .add_system(occupancy::occupancy_system())
+        .add_system(ai::ai_system())
         .add_system(random_move::random_move_system())
         .add_system(chasing::chasing_system())
+        .add_system(patrol::patrol_system())
+        .add_system(flee::flee_system())
+        .add_system(search::search_system())
         .flush()
         .add_system(use_item::use_item_system())
         .add_system(combat::combat_system())
         .flush()
         .add_system(movement::movement_system())
         .flush()
+        .add_system(traps::traps_system())
+        .add_system(hazards::hazards_system())
+        .flush()
         .add_system(fov::fov_system())
         .flush()
+        .add_system(end_turn::end_turn_system())
+        .build()
+}
+
+// drawn over the render schedule while the ai debug overlay is on
+pub fn build_debug_scheduler() -> Schedule {
+    Schedule::builder()
+        .add_system(ai_debug::ai_debug_system())
+        .build()
+}
+
+// run after the rules schedules each frame, only when there is a window
+pub fn build_render_scheduler() -> Schedule {
+    Schedule::builder()
         .add_system(map_render::map_render_system())
         .add_system(entity_render::entity_render_system())
         .add_system(hud::hud_system())
-        .add_system(end_turn::end_turn_system())
+        .add_system(tooltips::tooltips_system())
         .build()
 }
@@ -1,23 +1,56 @@
 use crate::prelude::*;
 
-#[system(for_each)] // for each Entity with WantsToMove component
+#[system]
+#[read_component(WantsToMove)]
+#[read_component(Point)]
 #[read_component(Player)]
 #[read_component(FieldOfView)]
 pub fn movement(
-    entity: &Entity,
-    want_move: &WantsToMove,
     #[resource] map: &mut Map,
     #[resource] camera: &mut Camera,
-    ecs: &mut SubWorld,
+    #[resource] turn_state: &mut TurnState,
+    #[resource] occupancy: &mut Occupancy,
+    ecs: &SubWorld,
     commands: &mut CommandBuffer,
 ) {
-    if map.can_enter_tile(want_move.destination) {
-        // if the destination tile is walkable, add a Point component to the entity with the new position
-        commands.add_component(want_move.entity, want_move.destination);
+    // in the order the systems queued them, so the same turn always plays out the same way
+    let mut pending: Vec<WantsToMove> = <(Entity, &WantsToMove)>::query()
+        .iter(ecs)
+        .map(|(message, want_move)| {
+            // remove the WantsToMove message
+            commands.remove(*message);
+            *want_move
+        })
+        .collect();
 
-        // refresh the FOV of the entity that moved
-        // access the details of another component on entity outside of the query for_each
-        if let Ok(entry) = ecs.entry_ref(want_move.entity) {
+    // one step a turn, however many moves were asked for
+    let mut moved: Vec<Entity> = Vec::new();
+    // a move onto a tile someone is leaving waits for them to go first
+    // anything still blocked once no one else can move stays where it is
+    loop {
+        let waiting = pending.len();
+        pending.retain(|want_move| {
+            if moved.contains(&want_move.entity) || !map.can_enter_tile(want_move.destination) {
+                return false;
+            }
+            let blocked = matches!(occupancy.occupant(want_move.destination),
+                Some(other) if other != want_move.entity);
+            if blocked {
+                return true;
+            }
+            let Ok(entry) = ecs.entry_ref(want_move.entity) else {
+                return false;
+            };
+            if let Ok(from) = entry.get_component::<Point>() {
+                occupancy.step(want_move.entity, *from, want_move.destination);
+            }
+            moved.push(want_move.entity);
+
+            // the tile is walkable and free, add a Point component with the new position
+            commands.add_component(want_move.entity, want_move.destination);
+
+            // refresh the FOV of the entity that moved
+            // access the details of another component on entity outside of the query
             if let Ok(fov) = entry.get_component::<FieldOfView>() {
                 commands.add_component(want_move.entity, fov.clone_dirty());
 
@@ -25,14 +58,24 @@ pub fn movement(
                 if entry.get_component::<Player>().is_ok() {
                     camera.on_player_move(want_move.destination);
 
+                    // stepping onto stairs takes the player to another floor
+                    match map.tiles[map.point2d_to_index(want_move.destination)] {
+                        TileType::Exit => *turn_state = TurnState::NextLevel,
+                        TileType::UpStairs => *turn_state = TurnState::PreviousLevel,
+                        _ => {}
+                    }
+
                     // cumulatively add to revealed tiles
                     fov.visible_tiles.iter().for_each(|p| {
-                        map.revealed_tiles[map_idx(p.x, p.y)] = true;
+                        let idx = map.point2d_to_index(*p);
+                        map.revealed_tiles[idx] = true;
                     });
                 }
             }
+            false
+        });
+        if pending.len() == waiting {
+            break;
         }
     }
-    // remove the WantsToMove component
-    commands.remove(*entity);
 }
@@ -0,0 +1,14 @@
+use crate::prelude::*;
+
+// fresh index of who is standing where, so kills and spawns since the last moves are picked up
+#[system]
+#[read_component(Point)]
+#[read_component(Health)]
+pub fn occupancy(ecs: &SubWorld, #[resource] occupancy: &mut Occupancy) {
+    occupancy.rebuild(
+        <(Entity, &Point)>::query()
+            .filter(component::<Health>())
+            .iter(ecs)
+            .map(|(entity, pos)| (*entity, *pos)),
+    );
+}
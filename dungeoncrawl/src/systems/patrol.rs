@@ -0,0 +1,40 @@
+use crate::prelude::*;
+
+// patrols and guards going back to their post walk the shortest way there
+// monsters heading for the same spot share one flow field
+#[system]
+#[read_component(Point)]
+#[read_component(Brain)]
+#[read_component(Recovering)]
+pub fn patrol(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] map: &Map,
+    #[resource] flow_fields: &mut FlowFields,
+    #[resource] occupancy: &mut Occupancy,
+) {
+    <(Entity, &Point, &Brain)>::query()
+        .filter(!component::<Recovering>())
+        .iter(ecs)
+        .for_each(|(entity, pos, brain)| {
+            let Some(target) = brain.destination() else {
+                return;
+            };
+            if target == *pos || !map.in_bounds(target) {
+                return;
+            }
+            let Some(destination) = flow_fields.step_toward(target, *pos, map) else {
+                return;
+            };
+            // wait for whoever is in the way to move on
+            if occupancy.reserve(*entity, destination) {
+                commands.push((
+                    (),
+                    WantsToMove {
+                        entity: *entity,
+                        destination,
+                    },
+                ));
+            }
+        });
+}
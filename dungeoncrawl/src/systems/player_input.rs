@@ -10,42 +10,24 @@ use crate::prelude::*;
 pub fn player_input(
     ecs: &mut SubWorld,
     commands: &mut CommandBuffer,
-    #[resource] key: &Option<VirtualKeyCode>,
+    #[resource] map: &Map,
+    #[resource] action: &Option<Action>,
     #[resource] turn_state: &mut TurnState,
 ) {
     // Get the player entity and position first (short-lived borrow)
     let (player_entity, player_pos) = {
         let mut q = <(Entity, &Point)>::query().filter(component::<Player>());
         q.iter(ecs)
-            .find_map(|(e, p)| Some((*e, *p)))
+            .map(|(e, p)| (*e, *p))
+            .next()
             .expect("Player entity not found")
     };
 
-    // Convert key input into an Action enum
-    let action = if let Some(key) = *key {
-        match key {
-            // Movement keys
-            VirtualKeyCode::Left | VirtualKeyCode::A => Action::Move(Point::new(-1, 0)),
-            VirtualKeyCode::Right | VirtualKeyCode::D => Action::Move(Point::new(1, 0)),
-            VirtualKeyCode::Up | VirtualKeyCode::W => Action::Move(Point::new(0, -1)),
-            VirtualKeyCode::Down | VirtualKeyCode::S => Action::Move(Point::new(0, 1)),
-            // Pickup item
-            VirtualKeyCode::G => Action::PickupAt(player_pos),
-            // Use item from inventory (1-9 keys)
-            VirtualKeyCode::Key1 => Action::Use(0),
-            VirtualKeyCode::Key2 => Action::Use(1),
-            VirtualKeyCode::Key3 => Action::Use(2),
-            VirtualKeyCode::Key4 => Action::Use(3),
-            VirtualKeyCode::Key5 => Action::Use(4),
-            VirtualKeyCode::Key6 => Action::Use(5),
-            VirtualKeyCode::Key7 => Action::Use(6),
-            VirtualKeyCode::Key8 => Action::Use(7),
-            VirtualKeyCode::Key9 => Action::Use(8),
-            // No action for other keys
-            _ => Action::None,
-        }
-    } else {
-        Action::None
+    // the action was chosen outside the ECS, from a key press or a script
+    let action = match *action {
+        Some(action) => action,
+        // If there's no input, we do NOT set `turn_state` – just return early
+        None => return,
     };
 
     let mut did_something = false;
@@ -54,9 +36,9 @@ pub fn player_input(
     // Handle the action (all ECS borrows are scoped)
     match action {
         Action::Move(delta) => {
-            if delta != Point::zero() {
-                let destination = player_pos + delta;
-
+            let destination = player_pos + delta;
+            // a diagonal the map doesn't allow doesn't use up the turn
+            if map.is_step(player_pos, destination) {
                 let mut enemies = <(Entity, &Point)>::query().filter(component::<Enemy>());
                 let mut hit = false;
 
@@ -74,7 +56,16 @@ pub fn player_input(
                         ));
                     });
 
-                if !hit {
+                if !hit && map.is_closed_door(destination) {
+                    // bumping a door opens it, stepping through takes another turn
+                    commands.push((
+                        (),
+                        WantsToOpenDoor {
+                            entity: player_entity,
+                            position: destination,
+                        },
+                    ));
+                } else if !hit {
                     commands.push((
                         (),
                         WantsToMove {
@@ -88,24 +79,24 @@ pub fn player_input(
             }
         }
 
-        Action::PickupAt(pos) => {
+        Action::Pickup => {
             let mut items = <(Entity, &Item, &Point)>::query();
             items
                 .iter(ecs)
-                .filter(|(_, _, item_pos)| *item_pos == &pos)
+                .filter(|(_, _, item_pos)| *item_pos == &player_pos)
                 .for_each(|(entity, _, _)| {
                     commands.remove_component::<Point>(*entity);
                     commands.add_component(*entity, Carried(player_entity));
 
-                    if let Ok(e) = ecs.entry_ref(*entity) {
-                        if e.get_component::<Weapon>().is_ok() {
-                            <(Entity, &Carried, &Weapon)>::query()
-                                .iter(ecs)
-                                .filter(|(_, carried, _)| carried.0 == player_entity)
-                                .for_each(|(other_entity, _, _)| {
-                                    commands.remove(*other_entity);
-                                });
-                        }
+                    if let Ok(e) = ecs.entry_ref(*entity)
+                        && e.get_component::<Weapon>().is_ok()
+                    {
+                        <(Entity, &Carried, &Weapon)>::query()
+                            .iter(ecs)
+                            .filter(|(_, carried, _)| carried.0 == player_entity)
+                            .for_each(|(other_entity, _, _)| {
+                                commands.remove(*other_entity);
+                            });
                     }
                 });
 
@@ -126,10 +117,67 @@ pub fn player_input(
             }
         }
 
-        Action::None => {
-            // If there's no input, we do NOT set `turn_state` – just return early
+        Action::CloseDoor => {
+            // anything standing in the doorway keeps it open
+            let occupied: Vec<Point> = <&Point>::query().iter(ecs).copied().collect();
+            [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                .iter()
+                .map(|(dx, dy)| player_pos + Point::new(*dx, *dy))
+                .filter(|p| {
+                    map.try_idx(*p)
+                        .is_some_and(|idx| map.tiles[idx] == TileType::OpenDoor)
+                        && !occupied.contains(p)
+                })
+                .for_each(|position| {
+                    commands.push((
+                        (),
+                        WantsToCloseDoor {
+                            entity: player_entity,
+                            position,
+                        },
+                    ));
+                    did_something = true;
+                });
+        }
+
+        Action::Wait => did_something = true,
+
+        // taking the stairs is a step onto them, movement changes the floor
+        Action::Descend => {
+            let on_stairs = map
+                .try_idx(player_pos)
+                .is_some_and(|idx| map.tiles[idx] == TileType::Exit);
+            if on_stairs {
+                commands.push((
+                    (),
+                    WantsToMove {
+                        entity: player_entity,
+                        destination: player_pos,
+                    },
+                ));
+                did_something = true;
+            }
+        }
+
+        // Saving and loading need the whole world, so hand over to Game
+        Action::Save => {
+            *turn_state = TurnState::SaveGame;
             return;
         }
+
+        Action::Load => {
+            *turn_state = TurnState::LoadGame;
+            return;
+        }
+
+        // screens belong to the front end, nothing happens in the dungeon
+        Action::Inventory
+        | Action::Help
+        | Action::Restart
+        | Action::AiDebug
+        | Action::ReplayPause
+        | Action::ReplayStep
+        | Action::ReplayFastForward => return,
     }
 
     // Push the item activation as a deferred command
@@ -147,11 +195,3 @@ pub fn player_input(
         *turn_state = TurnState::PlayerTurn;
     }
 }
-
-// Internal enum to represent player actions
-enum Action {
-    None,
-    Move(Point),
-    PickupAt(Point),
-    Use(usize),
-}
@@ -2,58 +2,66 @@ use crate::prelude::*;
 
 #[system]
 #[read_component(Point)]
-#[read_component(MovingRandomly)]
-#[read_component(Health)]
+#[read_component(Brain)]
+#[read_component(Recovering)]
 #[read_component(Player)]
-pub fn random_move(ecs: &SubWorld, commands: &mut CommandBuffer) {
-    let mut movers = <(Entity, &Point, &MovingRandomly)>::query();
-    let mut positions = <(Entity, &Point, &Health)>::query();
-    movers.iter(ecs).for_each(|(entity, pos, _)| {
-        let mut rng = RandomNumberGenerator::new();
-        let destination = match rng.range(0, 4) {
-            0 => Point::new(-1, 0),
-            1 => Point::new(1, 0),
-            2 => Point::new(0, -1),
-            _ => Point::new(0, 1),
-        } + *pos;
-        let mut attacked = false;
-        // queries all entities, list ones in destination tile
-        // if has player component, attack
-        positions
-            .iter(ecs)
-            .filter(|(_, target_pos, _)| **target_pos == destination)
-            .for_each(|(victim, _, _)| {
-                if ecs
-                    .entry_ref(*victim)
-                    .unwrap()
-                    .get_component::<Player>()
-                    .is_ok()
-                {
+pub fn random_move(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] rng: &mut RandomNumberGenerator,
+    #[resource] map: &Map,
+    #[resource] occupancy: &mut Occupancy,
+) {
+    let mut movers = <(Entity, &Point, &Brain)>::query().filter(!component::<Recovering>());
+    let is_player = |victim: Entity| {
+        ecs.entry_ref(victim)
+            .is_ok_and(|entry| entry.get_component::<Player>().is_ok())
+    };
+    movers
+        .iter(ecs)
+        .filter(|(_, _, brain)| brain.state == AiState::Wander)
+        .for_each(|(entity, pos, _)| {
+            // shared seeded generator so monster moves replay with the run
+            // cardinal maps keep rolling 0..4 so their seeds play out as before
+            let directions = if map.diagonal { 8 } else { 4 };
+            let destination = match rng.range(0, directions) {
+                0 => Point::new(-1, 0),
+                1 => Point::new(1, 0),
+                2 => Point::new(0, -1),
+                3 => Point::new(0, 1),
+                4 => Point::new(-1, -1),
+                5 => Point::new(1, -1),
+                6 => Point::new(-1, 1),
+                _ => Point::new(1, 1),
+            } + *pos;
+            // no squeezing diagonally past a wall corner
+            if !map.is_step(*pos, destination) {
+                return;
+            }
+            match occupancy.occupant(destination) {
+                Some(victim) if is_player(victim) => {
                     commands.push((
                         (),
                         WantsToAttack {
                             attacker: *entity,
-                            victim: *victim,
+                            victim,
                         },
                     ));
                 }
-                attacked = true; // even if no attack launched to prevent moving on to tile with another enemy
-            });
-        if !attacked {
-            commands.push((
-                (),
-                WantsToMove {
-                    entity: *entity,
-                    destination,
-                },
-            ));
-        }
-        commands.push((
-            (),
-            WantsToMove {
-                entity: *entity,
-                destination,
-            },
-        ));
-    });
+                // another monster is in the way
+                Some(_) => {}
+                // free, as long as no one else has claimed it this turn
+                None => {
+                    if occupancy.reserve(*entity, destination) {
+                        commands.push((
+                            (),
+                            WantsToMove {
+                                entity: *entity,
+                                destination,
+                            },
+                        ));
+                    }
+                }
+            }
+        });
 }
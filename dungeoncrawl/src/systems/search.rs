@@ -0,0 +1,70 @@
+use crate::prelude::*;
+
+// monsters that lost sight of the player walk to where they last saw it and look around
+#[system]
+#[read_component(Point)]
+#[read_component(Brain)]
+#[read_component(Recovering)]
+#[read_component(LastSeenPlayer)]
+pub fn search(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] map: &Map,
+    #[resource] rng: &mut RandomNumberGenerator,
+    #[resource] flow_fields: &mut FlowFields,
+    #[resource] occupancy: &mut Occupancy,
+) {
+    <(Entity, &Point, &Brain, &LastSeenPlayer)>::query()
+        .filter(!component::<Recovering>())
+        .iter(ecs)
+        .filter(|(_, _, brain, _)| brain.state == AiState::Search)
+        .for_each(|(entity, pos, _, memory)| {
+            let distance = DistanceAlg::Pythagoras.distance2d(*pos, memory.position);
+            let destination = if distance > SEARCH_RADIUS as f32 {
+                let step = flow_fields.step_toward(memory.position, *pos, map);
+                if step.is_none() {
+                    // can't get there, so search around here instead
+                    commands.add_component(
+                        *entity,
+                        LastSeenPlayer {
+                            position: *pos,
+                            ..*memory
+                        },
+                    );
+                    return;
+                }
+                step
+            } else {
+                // poke around without straying from the spot, diagonally too when that's on
+                let directions = if map.diagonal { 8 } else { 4 };
+                let delta = match rng.range(0, directions) {
+                    0 => Point::new(-1, 0),
+                    1 => Point::new(1, 0),
+                    2 => Point::new(0, -1),
+                    3 => Point::new(0, 1),
+                    4 => Point::new(-1, -1),
+                    5 => Point::new(1, -1),
+                    6 => Point::new(-1, 1),
+                    _ => Point::new(1, 1),
+                };
+                Some(*pos + delta).filter(|p| {
+                    map.is_step(*pos, *p)
+                        && DistanceAlg::Pythagoras.distance2d(*p, memory.position)
+                            <= SEARCH_RADIUS as f32
+                })
+            };
+
+            let Some(destination) = destination else {
+                return;
+            };
+            if map.can_enter_tile(destination) && occupancy.reserve(*entity, destination) {
+                commands.push((
+                    (),
+                    WantsToMove {
+                        entity: *entity,
+                        destination,
+                    },
+                ));
+            }
+        });
+}
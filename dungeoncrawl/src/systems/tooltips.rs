@@ -17,7 +17,7 @@ pub fn tooltips(ecs: &SubWorld, #[resource] mouse_pos: &Point, #[resource] camer
     draw_batch.target(2);
 
     // only show tooltips for entities within the player's FOV
-    let player_fov = fov.iter(ecs).nth(0).unwrap();
+    let player_fov = fov.iter(ecs).next().unwrap();
     positions
         .iter(ecs)
         // filter to find entities at the mouse position and in FOV
@@ -0,0 +1,44 @@
+use crate::prelude::*;
+
+#[system]
+#[read_component(Trap)]
+#[read_component(Point)]
+#[read_component(Damage)]
+#[read_component(Player)]
+#[write_component(Health)]
+pub fn traps(ecs: &mut SubWorld, commands: &mut CommandBuffer) {
+    // collect first, do not modify ecs while iterating over it
+    let traps: Vec<(Entity, Point, i32)> = <(Entity, &Point, &Damage)>::query()
+        .filter(component::<Trap>())
+        .iter(ecs)
+        .map(|(entity, pos, damage)| (*entity, *pos, damage.0))
+        .collect();
+
+    traps.iter().for_each(|(trap, pos, damage)| {
+        let victims: Vec<(Entity, bool)> = <(Entity, &Point, &Health, Option<&Player>)>::query()
+            .iter(ecs)
+            .filter(|(_, victim_pos, _, _)| *victim_pos == pos)
+            .map(|(entity, _, _, player)| (*entity, player.is_some()))
+            .collect();
+
+        // nobody standing on it, the trap stays armed
+        if victims.is_empty() {
+            return;
+        }
+
+        victims.iter().for_each(|(victim, is_player)| {
+            if let Ok(health) = ecs
+                .entry_mut(*victim)
+                .unwrap()
+                .get_component_mut::<Health>()
+            {
+                health.current -= damage;
+                // the player is never removed, end_turn notices the loss
+                if health.current < 1 && !is_player {
+                    commands.remove(*victim);
+                }
+            }
+        });
+        commands.remove(*trap);
+    });
+}
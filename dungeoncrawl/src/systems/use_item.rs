@@ -5,6 +5,7 @@ use crate::prelude::*;
 #[read_component(ProvidesHealing)]
 #[write_component(Health)]
 #[read_component(ProvidesDungeonMap)]
+#[read_component(Key)]
 pub fn use_item(ecs: &mut SubWorld, commands: &mut CommandBuffer, #[resource] map: &mut Map) {
     // as system iterates through item effects, add healing events to this vec
     let mut healing_to_apply = Vec::<(Entity, i32)>::new();
@@ -16,6 +17,10 @@ pub fn use_item(ecs: &mut SubWorld, commands: &mut CommandBuffer, #[resource] ma
             .for_each(|(item_entity, activate)| {
                 // entry_ref returns reference to entity not returned from query, which we can use to get components
                 let item = ecs.entry_ref(activate.item);
+                // keys are only used up by the door they open
+                let is_key = item
+                    .as_ref()
+                    .is_ok_and(|item| item.get_component::<Key>().is_ok());
                 if let Ok(item) = item {
                     if let Ok(healing) = item.get_component::<ProvidesHealing>() {
                         // queue healing to apply after iteration
@@ -26,18 +31,20 @@ pub fn use_item(ecs: &mut SubWorld, commands: &mut CommandBuffer, #[resource] ma
                         map.revealed_tiles.iter_mut().for_each(|t| *t = true);
                     }
                 }
-                commands.remove(activate.item); // remove item after use
+                if !is_key {
+                    commands.remove(activate.item); // remove item after use
+                }
                 commands.remove(*item_entity);
             });
     }
 
     // apply healing after iteration to avoid mutable/immutable borrow issues
     for heal in healing_to_apply.iter() {
-        if let Ok(mut target) = ecs.entry_mut(heal.0) {
-            if let Ok(health) = target.get_component_mut::<Health>() {
-                health.current = i32::min(health.max, health.current + heal.1); // capped at max health
-                println!("You use a healing item, restoring {} hp.", heal.1);
-            }
+        if let Ok(mut target) = ecs.entry_mut(heal.0)
+            && let Ok(health) = target.get_component_mut::<Health>()
+        {
+            health.current = i32::min(health.max, health.current + heal.1); // capped at max health
+            println!("You use a healing item, restoring {} hp.", heal.1);
         }
     }
 }
@@ -1,4 +1,6 @@
-#[derive(Clone, Copy, Debug, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum TurnState {
     AwaitingInput,
     PlayerTurn,
@@ -6,4 +8,7 @@ pub enum TurnState {
     GameOver,
     Victory,
     NextLevel,
+    PreviousLevel,
+    SaveGame,
+    LoadGame,
 }
@@ -0,0 +1,244 @@
+mod common;
+
+use common::*;
+use dungeoncrawl::prelude::*;
+
+fn brain(ecs: &World, entity: Entity) -> Brain {
+    *ecs.entry_ref(entity)
+        .unwrap()
+        .get_component::<Brain>()
+        .unwrap()
+}
+
+fn behaviour(calm: Calm, flee_below: i32) -> Behaviour {
+    Behaviour {
+        calm,
+        flee_below,
+        ..Behaviour::default()
+    }
+}
+
+fn memory(ecs: &World, entity: Entity) -> Option<LastSeenPlayer> {
+    ecs.entry_ref(entity)
+        .unwrap()
+        .get_component::<LastSeenPlayer>()
+        .ok()
+        .copied()
+}
+
+#[test]
+fn brains_chase_on_sight_and_settle_down_after() {
+    let home = Point::new(5, 5);
+    let player = Some(Point::new(8, 5));
+    let mut brain = Brain::new(behaviour(Calm::Wander, 0), home);
+    assert_eq!(brain.state, AiState::Wander);
+
+    brain.think(player, None, None);
+    assert_eq!(brain.state, AiState::Chase);
+    brain.think(None, None, None);
+    assert_eq!(brain.state, AiState::Wander);
+
+    let mut idle = Brain::new(Behaviour::default(), home);
+    assert_eq!(idle.state, AiState::Idle);
+    idle.think(player, None, None);
+    assert_eq!(idle.state, AiState::Chase);
+}
+
+#[test]
+fn wounded_monsters_run() {
+    let mut brain = Brain::new(behaviour(Calm::Idle, 50), Point::new(5, 5));
+    let player = Some(Point::new(6, 5));
+    brain.think(player, None, Some(&Health { current: 3, max: 4 }));
+    assert_eq!(brain.state, AiState::Chase);
+    brain.think(player, None, Some(&Health { current: 2, max: 4 }));
+    assert_eq!(brain.state, AiState::Flee);
+    // out of sight it calms down again
+    brain.think(None, None, Some(&Health { current: 2, max: 4 }));
+    assert_eq!(brain.state, AiState::Idle);
+}
+
+#[test]
+fn guards_stay_near_their_post() {
+    let home = Point::new(5, 5);
+    let mut brain = Brain::new(behaviour(Calm::Guard { leash: 3 }, 0), home);
+    brain.think(Some(Point::new(7, 5)), None, None);
+    assert_eq!(brain.state, AiState::Chase);
+    brain.think(Some(Point::new(12, 5)), None, None);
+    assert_eq!(brain.state, AiState::Guard);
+    assert_eq!(brain.destination(), Some(home));
+}
+
+#[test]
+fn guards_walk_back_to_their_post() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(40, 40));
+    let post = Point::new(10, 10);
+    let guard = push_brain(&mut ecs, post, 5, behaviour(Calm::Guard { leash: 3 }, 0));
+    <&mut Point>::query()
+        .filter(component::<Enemy>())
+        .iter_mut(&mut ecs)
+        .for_each(|pos| *pos = Point::new(13, 10));
+
+    for _ in 0..3 {
+        monster_turn(&mut ecs, &mut resources);
+    }
+    assert_eq!(position(&ecs, guard), post);
+    monster_turn(&mut ecs, &mut resources);
+    assert_eq!(position(&ecs, guard), post, "it stays once it's back");
+}
+
+#[test]
+fn patrols_move_around_home() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(60, 40));
+    let home = Point::new(10, 10);
+    let radius = 4;
+    let orc = push_brain(&mut ecs, home, 5, behaviour(Calm::Patrol { radius }, 0));
+
+    let mut visited = vec![home];
+    for _ in 0..30 {
+        monster_turn(&mut ecs, &mut resources);
+        let pos = position(&ecs, orc);
+        assert!(
+            (pos.x - home.x).abs() <= radius && (pos.y - home.y).abs() <= radius,
+            "{:?} strayed from home",
+            pos
+        );
+        if !visited.contains(&pos) {
+            visited.push(pos);
+        }
+    }
+    assert!(visited.len() > 3, "the patrol hardly moved");
+    assert!(matches!(brain(&ecs, orc).state, AiState::Patrol(_)));
+}
+
+#[test]
+fn fleeing_monsters_keep_their_distance() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(10, 10));
+    let player = player_entity(&ecs);
+    let coward = push_brain(&mut ecs, Point::new(12, 10), 4, behaviour(Calm::Idle, 50));
+    ecs.entry(coward)
+        .unwrap()
+        .get_component_mut::<Health>()
+        .unwrap()
+        .current = 1;
+
+    let start = DistanceAlg::Pythagoras.distance2d(Point::new(10, 10), Point::new(12, 10));
+    monster_turn(&mut ecs, &mut resources);
+    assert_eq!(brain(&ecs, coward).state, AiState::Flee);
+    let after = DistanceAlg::Pythagoras.distance2d(player_pos(&ecs), position(&ecs, coward));
+    assert!(after > start);
+    assert_eq!(health(&ecs, player).current, 100);
+}
+
+#[test]
+fn cornered_monsters_fight_back() {
+    let (mut ecs, mut resources) = test_world();
+    // a dead end, the player blocks the only way out
+    {
+        let mut map = resources.get_mut::<Map>().unwrap();
+        map.tiles.iter_mut().for_each(|t| *t = TileType::Wall);
+        for x in 5..8 {
+            let idx = map.point2d_to_index(Point::new(x, 5));
+            map.tiles[idx] = TileType::Floor;
+        }
+    }
+    spawn_player(&mut ecs, Point::new(6, 5));
+    let player = player_entity(&ecs);
+    let rat = push_brain(&mut ecs, Point::new(5, 5), 4, behaviour(Calm::Idle, 100));
+
+    monster_turn(&mut ecs, &mut resources);
+
+    assert_eq!(brain(&ecs, rat).state, AiState::Flee);
+    assert_eq!(health(&ecs, player).current, 99);
+}
+
+#[test]
+fn templates_pick_their_behaviour() {
+    let templates = Templates::load();
+    let behaviour_of = |name: &str| {
+        templates
+            .entities
+            .iter()
+            .find(|t| t.name == name)
+            .map(|t| t.behaviour)
+            .unwrap()
+    };
+    assert_eq!(behaviour_of("Goblin").calm, Calm::Wander);
+    assert!(matches!(behaviour_of("Orc").calm, Calm::Patrol { .. }));
+    assert!(matches!(behaviour_of("Ogre").calm, Calm::Guard { .. }));
+    assert_eq!(behaviour_of("Ettin"), Behaviour::default());
+
+    // every spawned enemy gets a brain
+    let game = Game::new(Some(GameSeed(3)));
+    let enemies = <&Enemy>::query().iter(&game.ecs).count();
+    assert_eq!(<&Brain>::query().iter(&game.ecs).count(), enemies);
+}
+
+#[test]
+fn lost_players_are_searched_for() {
+    let mut brain = Brain::new(behaviour(Calm::Wander, 0), Point::new(5, 5));
+    brain.think(Some(Point::new(8, 5)), None, None);
+    brain.think(None, Some(Point::new(8, 5)), None);
+    assert_eq!(brain.state, AiState::Search);
+    // once the memory runs out it goes back to what it was doing
+    brain.think(None, None, None);
+    assert_eq!(brain.state, AiState::Wander);
+
+    // a wandering monster doesn't start searching for a player it never chased
+    brain.think(None, Some(Point::new(8, 5)), None);
+    assert_eq!(brain.state, AiState::Wander);
+}
+
+#[test]
+fn monsters_walk_to_where_they_lost_the_player() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(60, 40));
+    let orc = push_brain(&mut ecs, Point::new(10, 10), 5, Behaviour::default());
+    let last_seen = Point::new(20, 10);
+    {
+        let mut entry = ecs.entry(orc).unwrap();
+        entry.get_component_mut::<Brain>().unwrap().state = AiState::Chase;
+        entry.add_component(LastSeenPlayer {
+            position: last_seen,
+            turns_left: 5,
+        });
+    }
+
+    let mut distance = 10;
+    while distance > SEARCH_RADIUS {
+        monster_turn(&mut ecs, &mut resources);
+        assert_eq!(brain(&ecs, orc).state, AiState::Search);
+        let now = (position(&ecs, orc).x - last_seen.x).abs();
+        assert!(now < distance, "it stopped heading for the player");
+        distance = now;
+    }
+    // the search only counts down once it gets there
+    assert_eq!(memory(&ecs, orc).unwrap().turns_left, 5);
+}
+
+#[test]
+fn searches_give_up_after_their_turns() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(13, 10));
+    let orc = push_brain(&mut ecs, Point::new(10, 10), 5, Behaviour::default());
+    monster_turn(&mut ecs, &mut resources);
+    assert_eq!(brain(&ecs, orc).state, AiState::Chase);
+    assert!(memory(&ecs, orc).is_some());
+
+    // the player slips away out of sight
+    set_player_pos(&mut ecs, Point::new(60, 40));
+    monster_turn(&mut ecs, &mut resources);
+    assert_eq!(brain(&ecs, orc).state, AiState::Search);
+    let near = |ecs: &World| {
+        DistanceAlg::Pythagoras.distance2d(position(ecs, orc), Point::new(13, 10))
+            <= SEARCH_RADIUS as f32
+    };
+    for _ in 0..Behaviour::default().search_turns {
+        assert!(near(&ecs), "the search wandered off");
+        monster_turn(&mut ecs, &mut resources);
+    }
+    assert_eq!(brain(&ecs, orc).state, AiState::Idle);
+    assert!(memory(&ecs, orc).is_none());
+}
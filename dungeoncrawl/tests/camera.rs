@@ -0,0 +1,21 @@
+use dungeoncrawl::prelude::*;
+
+#[test]
+fn small_maps_keep_the_player_centred() {
+    let map = Map::new(DISPLAY_WIDTH, DISPLAY_HEIGHT);
+    let camera = Camera::new(Point::new(3, 4), &map);
+    assert_eq!(camera.left_x, 3 - DISPLAY_WIDTH / 2);
+    assert_eq!(camera.top_y, 4 - DISPLAY_HEIGHT / 2);
+}
+
+#[test]
+fn large_maps_stop_scrolling_at_their_edges() {
+    let map = Map::new(MAP_WIDTH, MAP_HEIGHT);
+    let mut camera = Camera::new(Point::new(1, 1), &map);
+    assert_eq!((camera.left_x, camera.top_y), (0, 0));
+    camera.on_player_move(Point::new(MAP_WIDTH / 2, MAP_HEIGHT / 2));
+    assert_eq!(camera.left_x, MAP_WIDTH / 2 - DISPLAY_WIDTH / 2);
+    camera.on_player_move(Point::new(MAP_WIDTH - 1, MAP_HEIGHT - 1));
+    assert_eq!(camera.right_x, MAP_WIDTH);
+    assert_eq!(camera.bottom_y, MAP_HEIGHT);
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use common::*;
+use dungeoncrawl::prelude::*;
+use std::sync::Arc;
+
+fn campaign(grail_floor: u32, floors: usize) -> Campaign {
+    Campaign {
+        grail_floor,
+        floors: vec![Floor::any(MAP_WIDTH, MAP_HEIGHT); floors],
+        architect: None,
+        diagonal_movement: false,
+    }
+}
+
+#[test]
+fn shipped_campaign_is_valid() {
+    let campaign = Campaign::load();
+    assert!(campaign.check().is_ok());
+}
+
+#[test]
+fn grail_floor_must_exist() {
+    assert!(campaign(3, 3).check().is_err());
+    assert!(campaign(2, 3).check().is_ok());
+}
+
+#[test]
+fn unknown_names_are_rejected() {
+    let mut bad_architect = campaign(0, 1);
+    bad_architect.floors[0].architects = vec![("maze".to_string(), 1)];
+    assert!(bad_architect.check().is_err());
+
+    let mut bad_theme = campaign(0, 1);
+    bad_theme.floors[0].themes = vec!["lava".to_string()];
+    assert!(bad_theme.check().is_err());
+}
+
+#[test]
+fn single_floor_campaign_starts_next_to_the_grail() {
+    let mut game = Game::with_campaign(Some(GameSeed(5)), campaign(0, 1));
+    game.recording = None;
+
+    assert_eq!(map_level(&game.ecs), 0);
+    assert_eq!(<&Grail>::query().iter(&game.ecs).count(), 1);
+    let map = game.resources.get::<Map>().unwrap();
+    assert!(!map.tiles.contains(&TileType::Exit));
+}
+
+#[test]
+fn floor_size_comes_from_the_campaign() {
+    let mut small = campaign(0, 1);
+    small.floors[0].width = 40;
+    small.floors[0].height = 30;
+    let game = Game::with_campaign(Some(GameSeed(5)), small);
+
+    let map = game.resources.get::<Map>().unwrap();
+    assert_eq!((map.width, map.height), (40, 30));
+}
+
+#[test]
+fn monster_density_scales_spawns() {
+    let prefabs = Arc::new(Prefabs::load());
+    let mut floor = Floor::any(MAP_WIDTH, MAP_HEIGHT);
+    floor.architects = vec![("automata".to_string(), 1)];
+    let normal = MapBuilder::for_floor(&mut GameSeed(9).level_rng(0), &floor, &prefabs);
+    floor.monster_density = 0.5;
+    let sparse = MapBuilder::for_floor(&mut GameSeed(9).level_rng(0), &floor, &prefabs);
+    floor.monster_density = 0.0;
+    let empty = MapBuilder::for_floor(&mut GameSeed(9).level_rng(0), &floor, &prefabs);
+
+    assert!(sparse.monster_spawns.len() < normal.monster_spawns.len());
+    assert!(empty.monster_spawns.is_empty());
+}
+
+#[test]
+fn forced_architect_must_be_registered() {
+    let mut forced = campaign(0, 1);
+    forced.architect = Some("empty".to_string());
+    assert!(forced.check().is_ok());
+    forced.architect = Some("maze".to_string());
+    assert!(forced.check().is_err());
+}
+
+#[test]
+fn floors_without_weight_are_rejected() {
+    let mut unweighted = campaign(0, 1);
+    unweighted.floors[0].architects = vec![("rooms".to_string(), 0)];
+    assert!(unweighted.check().is_err());
+}
+
+#[test]
+fn floors_too_small_to_build_are_rejected() {
+    let mut tiny = campaign(0, 1);
+    tiny.floors[0].width = MIN_FLOOR_SIZE;
+    tiny.floors[0].height = MIN_FLOOR_SIZE;
+    assert!(tiny.check().is_ok());
+    tiny.floors[0].width = MIN_FLOOR_SIZE - 1;
+    assert!(tiny.check().is_err());
+}
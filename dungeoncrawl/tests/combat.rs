@@ -0,0 +1,83 @@
+mod common;
+
+use common::*;
+use dungeoncrawl::prelude::*;
+
+#[test]
+fn attack_uses_base_damage() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(10, 10));
+    let player = player_entity(&ecs);
+    let monster = push_monster(&mut ecs, Point::new(11, 10), 5, 1);
+    ecs.push((
+        (),
+        WantsToAttack {
+            attacker: player,
+            victim: monster,
+        },
+    ));
+
+    run_player_turn(&mut ecs, &mut resources);
+
+    assert_eq!(health(&ecs, monster).current, 4);
+}
+
+#[test]
+fn carried_weapon_adds_to_damage() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(10, 10));
+    let player = player_entity(&ecs);
+    ecs.push((Item, Weapon, Damage(2), Carried(player)));
+    let monster = push_monster(&mut ecs, Point::new(11, 10), 10, 1);
+    ecs.push((
+        (),
+        WantsToAttack {
+            attacker: player,
+            victim: monster,
+        },
+    ));
+
+    run_player_turn(&mut ecs, &mut resources);
+
+    assert_eq!(health(&ecs, monster).current, 10 - 3);
+}
+
+#[test]
+fn killed_monster_is_removed() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(10, 10));
+    let player = player_entity(&ecs);
+    let monster = push_monster(&mut ecs, Point::new(11, 10), 1, 1);
+    ecs.push((
+        (),
+        WantsToAttack {
+            attacker: player,
+            victim: monster,
+        },
+    ));
+
+    run_player_turn(&mut ecs, &mut resources);
+
+    assert!(!ecs.contains(monster));
+}
+
+#[test]
+fn monster_attack_hurts_player_but_never_removes_them() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(10, 10));
+    let player = player_entity(&ecs);
+    let monster = push_monster(&mut ecs, Point::new(11, 10), 5, 150);
+    ecs.push((
+        (),
+        WantsToAttack {
+            attacker: monster,
+            victim: player,
+        },
+    ));
+
+    run_player_turn(&mut ecs, &mut resources);
+
+    assert!(ecs.contains(player));
+    assert!(health(&ecs, player).current < 1);
+    assert_eq!(*resources.get::<TurnState>().unwrap(), TurnState::GameOver);
+}
@@ -0,0 +1,119 @@
+// helpers shared by the integration tests, not every test file uses all of them
+#![allow(dead_code)]
+
+use dungeoncrawl::prelude::*;
+
+// an all floor map plus the resources the rules schedules read
+pub fn test_world() -> (World, Resources) {
+    let mut resources = Resources::default();
+    let map = Map::new(MAP_WIDTH, MAP_HEIGHT);
+    resources.insert(Camera::new(Point::new(10, 10), &map));
+    resources.insert(map);
+    resources.insert(TurnState::PlayerTurn);
+    resources.insert(GameSeed(1));
+    resources.insert(GameSeed(1).ai_rng());
+    resources.insert(FlowFields::default());
+    resources.insert(Occupancy::default());
+    resources.insert(MessageLog::default());
+    resources.insert(None::<Action>);
+    (World::default(), resources)
+}
+
+pub fn player_entity(ecs: &World) -> Entity {
+    *<Entity>::query()
+        .filter(component::<Player>())
+        .iter(ecs)
+        .next()
+        .expect("no player in world")
+}
+
+pub fn player_pos(ecs: &World) -> Point {
+    *<&Point>::query()
+        .filter(component::<Player>())
+        .iter(ecs)
+        .next()
+        .expect("no player in world")
+}
+
+pub fn set_player_pos(ecs: &mut World, pos: Point) {
+    <&mut Point>::query()
+        .filter(component::<Player>())
+        .iter_mut(ecs)
+        .for_each(|p| *p = pos);
+}
+
+pub fn map_level(ecs: &World) -> u32 {
+    <&Player>::query()
+        .iter(ecs)
+        .map(|player| player.map_level)
+        .next()
+        .expect("no player in world")
+}
+
+pub fn push_monster(ecs: &mut World, pos: Point, hp: i32, damage: i32) -> Entity {
+    ecs.push((
+        Enemy,
+        pos,
+        Health {
+            current: hp,
+            max: hp,
+        },
+        Damage(damage),
+    ))
+}
+
+pub fn push_brain(ecs: &mut World, pos: Point, hp: i32, behaviour: Behaviour) -> Entity {
+    let monster = push_monster(ecs, pos, hp, 1);
+    let mut entry = ecs.entry(monster).unwrap();
+    entry.add_component(FieldOfView::new(6));
+    entry.add_component(Brain::new(behaviour, pos));
+    monster
+}
+
+// a chaser that can already see the player, so spawn the player first
+// speed gives it energy to act by, without it it acts every pass
+pub fn push_chaser(ecs: &mut World, pos: Point, speed: Option<i32>) -> Entity {
+    let mut fov = FieldOfView::new(6);
+    fov.visible_tiles.insert(player_pos(ecs));
+    let monster = push_monster(ecs, pos, 5, 1);
+    let mut entry = ecs.entry(monster).unwrap();
+    entry.add_component(fov);
+    entry.add_component(Brain::new(Behaviour::default(), pos));
+    if let Some(speed) = speed {
+        entry.add_component(Energy::new(speed));
+    }
+    monster
+}
+
+pub fn position(ecs: &World, entity: Entity) -> Point {
+    *ecs.entry_ref(entity)
+        .expect("entity was removed")
+        .get_component::<Point>()
+        .expect("entity has no position")
+}
+
+pub fn health(ecs: &World, entity: Entity) -> Health {
+    *ecs.entry_ref(entity)
+        .expect("entity was removed")
+        .get_component::<Health>()
+        .expect("entity has no health")
+}
+
+pub fn run_player_turn(ecs: &mut World, resources: &mut Resources) {
+    build_player_scheduler().execute(ecs, resources);
+}
+
+// the action goes through input first, then the player's turn plays out
+pub fn act(ecs: &mut World, resources: &mut Resources, action: Action) {
+    resources.insert(TurnState::AwaitingInput);
+    resources.insert(Some(action));
+    build_input_scheduler().execute(ecs, resources);
+    run_player_turn(ecs, resources);
+}
+
+// the monster turn as the game runs it, fov first so monsters see where everyone is
+pub fn monster_turn(ecs: &mut World, resources: &mut Resources) {
+    resources.insert(TurnState::EnemyTurn);
+    build_input_scheduler().execute(ecs, resources);
+    build_enemy_scheduler().execute(ecs, resources);
+}
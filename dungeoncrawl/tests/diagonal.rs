@@ -0,0 +1,178 @@
+mod common;
+
+use common::*;
+use dungeoncrawl::prelude::*;
+
+fn set_diagonal(resources: &Resources, on: bool) {
+    resources.get_mut::<Map>().unwrap().diagonal = on;
+}
+
+#[test]
+fn diagonal_exits_cost_more() {
+    let mut map = Map::new(10, 10);
+    let centre = map.point2d_to_index(Point::new(5, 5));
+    assert_eq!(map.get_available_exits(centre).len(), 4);
+
+    map.diagonal = true;
+    let exits = map.get_available_exits(centre);
+    assert_eq!(exits.len(), 8);
+    let corner = map.point2d_to_index(Point::new(6, 6));
+    let (_, cost) = exits.iter().find(|(idx, _)| *idx == corner).unwrap();
+    assert!((cost - std::f32::consts::SQRT_2).abs() < 0.001);
+}
+
+#[test]
+fn diagonals_do_not_cut_corners() {
+    let mut map = Map::new(10, 10);
+    map.diagonal = true;
+    assert!(map.is_step(Point::new(5, 5), Point::new(6, 6)));
+    assert!(!map.is_step(Point::new(5, 5), Point::new(7, 5)));
+
+    let idx = map.point2d_to_index(Point::new(6, 5));
+    map.tiles[idx] = TileType::Wall;
+    assert!(!map.is_step(Point::new(5, 5), Point::new(6, 6)));
+    assert!(map.is_step(Point::new(5, 5), Point::new(4, 6)));
+}
+
+#[test]
+fn diagonal_keys_only_move_when_turned_on() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(10, 10));
+
+    resources.insert(TurnState::AwaitingInput);
+    resources.insert(Some(Action::Move(Point::new(1, 1))));
+    build_input_scheduler().execute(&mut ecs, &mut resources);
+    assert_eq!(
+        *resources.get::<TurnState>().unwrap(),
+        TurnState::AwaitingInput,
+        "an eight way key shouldn't use up the turn"
+    );
+
+    set_diagonal(&resources, true);
+    act(&mut ecs, &mut resources, Action::Move(Point::new(1, 1)));
+    assert_eq!(player_pos(&ecs), Point::new(11, 11));
+}
+
+#[test]
+fn chasers_attack_diagonally_only_in_diagonal_mode() {
+    for on in [false, true] {
+        let (mut ecs, mut resources) = test_world();
+        set_diagonal(&resources, on);
+        spawn_player(&mut ecs, Point::new(10, 10));
+        let player = player_entity(&ecs);
+        let monster = push_chaser(&mut ecs, Point::new(11, 11), None);
+
+        resources.insert(TurnState::EnemyTurn);
+        build_enemy_scheduler().execute(&mut ecs, &mut resources);
+
+        let monster_pos = position(&ecs, monster);
+        if on {
+            assert_eq!(health(&ecs, player).current, 99);
+            assert_eq!(monster_pos, Point::new(11, 11));
+        } else {
+            // it has to step round to a side first
+            assert_eq!(health(&ecs, player).current, 100);
+            assert_ne!(monster_pos, Point::new(11, 11));
+        }
+    }
+}
+
+#[test]
+fn attacks_need_the_victim_within_a_step() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(10, 10));
+    let player = player_entity(&ecs);
+    let monster = push_monster(&mut ecs, Point::new(13, 10), 5, 1);
+    ecs.push((
+        (),
+        WantsToAttack {
+            attacker: player,
+            victim: monster,
+        },
+    ));
+
+    run_player_turn(&mut ecs, &mut resources);
+
+    assert_eq!(health(&ecs, monster).current, 5);
+    assert_eq!(<&WantsToAttack>::query().iter(&ecs).count(), 0);
+}
+
+#[test]
+fn numpad_and_vi_keys_move() {
+    let moves = [
+        (
+            VirtualKeyCode::Numpad7,
+            VirtualKeyCode::Y,
+            Point::new(-1, -1),
+        ),
+        (
+            VirtualKeyCode::Numpad8,
+            VirtualKeyCode::K,
+            Point::new(0, -1),
+        ),
+        (
+            VirtualKeyCode::Numpad9,
+            VirtualKeyCode::U,
+            Point::new(1, -1),
+        ),
+        (
+            VirtualKeyCode::Numpad4,
+            VirtualKeyCode::H,
+            Point::new(-1, 0),
+        ),
+        (VirtualKeyCode::Numpad6, VirtualKeyCode::L, Point::new(1, 0)),
+        (
+            VirtualKeyCode::Numpad1,
+            VirtualKeyCode::B,
+            Point::new(-1, 1),
+        ),
+        (VirtualKeyCode::Numpad2, VirtualKeyCode::J, Point::new(0, 1)),
+        (VirtualKeyCode::Numpad3, VirtualKeyCode::N, Point::new(1, 1)),
+    ];
+    let keymap = Keymap::load();
+    for (numpad, vi, delta) in moves {
+        assert_eq!(keymap.action(numpad), Some(Action::Move(delta)));
+        assert_eq!(keymap.action(vi), Some(Action::Move(delta)));
+    }
+}
+
+#[test]
+fn replays_remember_the_movement_mode() {
+    let mut campaign = Campaign::load();
+    assert!(
+        !campaign.diagonal_movement,
+        "the shipped campaign is four way"
+    );
+
+    campaign.diagonal_movement = true;
+    let replay = Replay::new(GameSeed(1), &campaign);
+    let mut watched = Campaign::load();
+    replay.apply_to(&mut watched);
+    assert!(watched.diagonal_movement);
+}
+
+#[test]
+fn searchers_look_around_diagonally_in_diagonal_mode() {
+    let (mut ecs, mut resources) = test_world();
+    set_diagonal(&resources, true);
+    spawn_player(&mut ecs, Point::new(60, 40));
+    let spot = Point::new(10, 10);
+    let orc = push_brain(&mut ecs, spot, 5, Behaviour::default());
+    {
+        let mut entry = ecs.entry(orc).unwrap();
+        entry.get_component_mut::<Brain>().unwrap().state = AiState::Search;
+        entry.add_component(LastSeenPlayer {
+            position: spot,
+            turns_left: 50,
+        });
+    }
+
+    let mut diagonal = false;
+    for _ in 0..20 {
+        let before = position(&ecs, orc);
+        monster_turn(&mut ecs, &mut resources);
+        let step = position(&ecs, orc) - before;
+        diagonal |= step.x != 0 && step.y != 0;
+    }
+    assert!(diagonal, "the search only went along the axes");
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use common::*;
+use dungeoncrawl::prelude::*;
+
+const DOOR: Point = Point { x: 11, y: 10 };
+
+fn set_tile(resources: &Resources, pos: Point, tile: TileType) {
+    let mut map = resources.get_mut::<Map>().unwrap();
+    let idx = map.point2d_to_index(pos);
+    map.tiles[idx] = tile;
+}
+
+fn tile(resources: &Resources, pos: Point) -> TileType {
+    let map = resources.get::<Map>().unwrap();
+    map.tiles[map.point2d_to_index(pos)]
+}
+
+fn open_door(ecs: &mut World, resources: &mut Resources) {
+    let player = player_entity(ecs);
+    ecs.push((
+        (),
+        WantsToOpenDoor {
+            entity: player,
+            position: DOOR,
+        },
+    ));
+    run_player_turn(ecs, resources);
+}
+
+#[test]
+fn closed_doors_block_movement_and_sight() {
+    // a dead end corridor running east with a door in it
+    let mut map = Map::new(20, 20);
+    map.tiles.iter_mut().for_each(|t| *t = TileType::Wall);
+    for x in 9..16 {
+        let idx = map.point2d_to_index(Point::new(x, 10));
+        map.tiles[idx] = TileType::Floor;
+    }
+    let door = map.point2d_to_index(DOOR);
+    let behind = Point::new(12, 10);
+
+    for (door_tile, open) in [
+        (TileType::Door, false),
+        (TileType::LockedDoor, false),
+        (TileType::OpenDoor, true),
+    ] {
+        map.tiles[door] = door_tile;
+        assert_eq!(map.can_enter_tile(DOOR), open);
+        assert_eq!(map.is_opaque(door), !open);
+        let seen = field_of_view_set(Point::new(10, 10), 5, &map);
+        assert_eq!(seen.contains(&behind), open);
+        let dijkstra_map = DijkstraMap::new(20, 20, &[map.point2d_to_index(behind)], &map, 1024.0);
+        let reached = dijkstra_map.map[map.point2d_to_index(Point::new(10, 10))] < f32::MAX;
+        assert_eq!(reached, open, "monsters path through open doors only");
+    }
+
+    // stairs no longer hide what is behind them
+    map.tiles[door] = TileType::Exit;
+    assert!(!map.is_opaque(door));
+}
+
+#[test]
+fn walking_into_a_door_opens_it() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(10, 10));
+    set_tile(&resources, DOOR, TileType::Door);
+
+    act(&mut ecs, &mut resources, Action::Move(Point::new(1, 0)));
+    assert!(tile(&resources, DOOR) == TileType::OpenDoor);
+    // opening took the turn, stepping through is the next one
+    assert_eq!(player_pos(&ecs), Point::new(10, 10));
+
+    act(&mut ecs, &mut resources, Action::Move(Point::new(1, 0)));
+    assert_eq!(player_pos(&ecs), DOOR);
+}
+
+#[test]
+fn locked_doors_need_a_key() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(10, 10));
+    let player = player_entity(&ecs);
+    set_tile(&resources, DOOR, TileType::LockedDoor);
+
+    open_door(&mut ecs, &mut resources);
+    assert!(tile(&resources, DOOR) == TileType::LockedDoor);
+    assert_eq!(
+        resources.get::<MessageLog>().unwrap().latest(),
+        Some("The door is locked.")
+    );
+
+    let key = ecs.push((Item, Key, Carried(player)));
+    open_door(&mut ecs, &mut resources);
+    assert!(tile(&resources, DOOR) == TileType::OpenDoor);
+    assert!(ecs.entry_ref(key).is_err(), "the key is used up");
+}
+
+#[test]
+fn using_a_key_from_the_inventory_keeps_it() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(10, 10));
+    let player = player_entity(&ecs);
+    let key = ecs.push((Item, Key, Carried(player)));
+
+    act(&mut ecs, &mut resources, Action::Use(0));
+
+    assert!(ecs.entry_ref(key).is_ok());
+}
+
+#[test]
+fn close_action_shuts_neighbouring_doors() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(10, 10));
+    set_tile(&resources, DOOR, TileType::OpenDoor);
+
+    // something in the doorway keeps it open, and the turn isn't used
+    let monster = push_monster(&mut ecs, DOOR, 1, 1);
+    resources.insert(TurnState::AwaitingInput);
+    resources.insert(Some(Action::CloseDoor));
+    build_input_scheduler().execute(&mut ecs, &mut resources);
+    assert_eq!(
+        *resources.get::<TurnState>().unwrap(),
+        TurnState::AwaitingInput
+    );
+    ecs.remove(monster);
+
+    act(&mut ecs, &mut resources, Action::CloseDoor);
+    assert!(tile(&resources, DOOR) == TileType::Door);
+}
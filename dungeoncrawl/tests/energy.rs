@@ -0,0 +1,118 @@
+mod common;
+
+use common::*;
+use dungeoncrawl::prelude::*;
+
+fn energy(ecs: &World, entity: Entity) -> Energy {
+    *ecs.entry_ref(entity)
+        .unwrap()
+        .get_component::<Energy>()
+        .unwrap()
+}
+
+// the player's turn then every pass the monsters have energy for, returns how many passes ran
+fn round(ecs: &mut World, resources: &mut Resources) -> usize {
+    resources.insert(TurnState::PlayerTurn);
+    run_player_turn(ecs, resources);
+    let mut passes = 0;
+    while *resources.get::<TurnState>().unwrap() == TurnState::EnemyTurn {
+        build_enemy_scheduler().execute(ecs, resources);
+        passes += 1;
+        assert!(passes < 10, "the monsters never ran out of energy");
+    }
+    passes
+}
+
+#[test]
+fn energy_is_gained_by_speed_and_spent_acting() {
+    let mut energy = Energy::new(50);
+    assert!(energy.is_ready());
+    energy.spend(ACTION_COST);
+    energy.gain();
+    assert!(!energy.is_ready());
+    energy.gain();
+    assert!(energy.is_ready());
+
+    assert_eq!(attack_cost(1), ACTION_COST);
+    assert!(attack_cost(HEAVY_DAMAGE) > ACTION_COST);
+}
+
+#[test]
+fn speed_decides_how_often_monsters_act() {
+    let (mut ecs, mut resources) = test_world();
+    let player = Point::new(10, 10);
+    spawn_player(&mut ecs, player);
+    let fast = push_chaser(&mut ecs, Point::new(16, 10), Some(200));
+    let normal = push_chaser(&mut ecs, Point::new(10, 16), Some(NORMAL_SPEED));
+    let slow = push_chaser(&mut ecs, Point::new(4, 10), Some(50));
+
+    // everything starts ready, so the first round is one pass
+    assert_eq!(round(&mut ecs, &mut resources), 1);
+    assert_eq!(round(&mut ecs, &mut resources), 2);
+
+    assert_eq!(position(&ecs, fast), Point::new(13, 10));
+    assert_eq!(position(&ecs, normal), Point::new(10, 14));
+    assert_eq!(position(&ecs, slow), Point::new(5, 10));
+    assert_eq!(
+        *resources.get::<TurnState>().unwrap(),
+        TurnState::AwaitingInput
+    );
+}
+
+#[test]
+fn heavy_attacks_give_the_monsters_an_extra_round() {
+    for (damage, passes) in [(1, 1), (HEAVY_DAMAGE, 2)] {
+        let (mut ecs, mut resources) = test_world();
+        spawn_player(&mut ecs, Point::new(10, 10));
+        let player = player_entity(&ecs);
+        ecs.entry(player).unwrap().add_component(Damage(damage));
+        let target = push_monster(&mut ecs, Point::new(11, 10), 20, 1);
+        ecs.push((
+            (),
+            WantsToAttack {
+                attacker: player,
+                victim: target,
+            },
+        ));
+
+        assert_eq!(round(&mut ecs, &mut resources), passes);
+        assert!(energy(&ecs, player).is_ready());
+    }
+}
+
+#[test]
+fn hazards_burn_once_a_round_however_fast() {
+    let (mut ecs, mut resources) = test_world();
+    let lava = Point::new(5, 5);
+    {
+        let mut map = resources.get_mut::<Map>().unwrap();
+        let idx = map.point2d_to_index(lava);
+        map.tiles[idx] = TileType::Lava;
+    }
+    spawn_player(&mut ecs, Point::new(20, 20));
+    let bat = push_monster(&mut ecs, lava, 10, 1);
+    ecs.entry(bat).unwrap().add_component(Energy::new(200));
+
+    assert_eq!(round(&mut ecs, &mut resources), 1);
+    assert_eq!(round(&mut ecs, &mut resources), 2);
+    assert_eq!(
+        health(&ecs, bat).current,
+        10 - 2 * TileType::Lava.hazard_damage()
+    );
+}
+
+#[test]
+fn templates_set_their_speed() {
+    let templates = Templates::load();
+    let speed_of = |name: &str| {
+        templates
+            .entities
+            .iter()
+            .find(|t| t.name == name)
+            .unwrap()
+            .speed
+    };
+    assert!(speed_of("Goblin") > NORMAL_SPEED);
+    assert_eq!(speed_of("Orc"), NORMAL_SPEED);
+    assert!(speed_of("Ettin") < NORMAL_SPEED);
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use common::*;
+use dungeoncrawl::prelude::*;
+
+fn rebuilds(resources: &Resources) -> usize {
+    resources.get::<FlowFields>().unwrap().rebuilds()
+}
+
+#[test]
+fn fields_are_kept_until_the_goals_move() {
+    let map = Map::new(20, 20);
+    let mut fields = FlowFields::default();
+
+    let idx = map.point2d_to_index(Point::new(5, 5));
+    assert_eq!(
+        fields.toward(Goal::Player, &[Point::new(5, 5)], &map).map[idx],
+        0.0
+    );
+    fields.toward(Goal::Player, &[Point::new(5, 5)], &map);
+    assert_eq!(fields.rebuilds(), 1);
+
+    fields.toward(Goal::Player, &[Point::new(6, 5)], &map);
+    assert_eq!(fields.rebuilds(), 2);
+}
+
+#[test]
+fn fields_are_rebuilt_when_the_map_changes() {
+    let mut map = Map::new(20, 20);
+    let mut fields = FlowFields::default();
+    let goal = [Point::new(5, 5)];
+    fields.toward(Goal::Player, &goal, &map);
+
+    // revealing tiles doesn't change a path
+    map.revealed_tiles[0] = true;
+    fields.toward(Goal::Player, &goal, &map);
+    assert_eq!(fields.rebuilds(), 1);
+
+    let wall = map.point2d_to_index(Point::new(6, 5));
+    map.tiles[wall] = TileType::Wall;
+    let field = fields.toward(Goal::Player, &goal, &map);
+    assert_eq!(field.map[wall], f32::MAX);
+    assert_eq!(fields.rebuilds(), 2);
+
+    map.diagonal = true;
+    fields.toward(Goal::Player, &goal, &map);
+    assert_eq!(fields.rebuilds(), 3);
+}
+
+#[test]
+fn each_goal_has_its_own_field() {
+    let map = Map::new(20, 20);
+    let mut fields = FlowFields::default();
+    let player = [Point::new(2, 2)];
+    let exits = [Point::new(18, 18), Point::new(2, 18)];
+
+    fields.toward(Goal::Player, &player, &map);
+    fields.toward(Goal::Exits, &exits, &map);
+    fields.toward(Goal::Player, &player, &map);
+    let to_exits = fields.toward(Goal::Exits, &exits, &map);
+    assert_eq!(to_exits.map[map.point2d_to_index(Point::new(2, 17))], 1.0);
+    assert_eq!(fields.rebuilds(), 2);
+}
+
+#[test]
+fn the_safety_field_leads_away_from_the_player() {
+    let map = Map::new(40, 40);
+    let mut fields = FlowFields::default();
+    let player = Point::new(20, 20);
+    let safety = fields.safety(player, &map);
+
+    let start = Point::new(21, 20);
+    let step = DijkstraMap::find_lowest_exit(safety, map.point2d_to_index(start), &map)
+        .map(|idx| map.index_to_point2d(idx))
+        .unwrap();
+    let distance = |p| DistanceAlg::Manhattan.distance2d(player, p);
+    assert!(distance(step) > distance(start));
+    // far enough away is already safe
+    assert_eq!(safety.map[map.point2d_to_index(Point::new(30, 20))], 0.0);
+}
+
+#[test]
+fn safety_is_the_furthest_reachable_tile_on_small_maps() {
+    let map = Map::new(4, 1);
+    let mut fields = FlowFields::default();
+    let safety = fields.safety(Point::new(0, 0), &map);
+    assert_eq!(safety.map[3], 0.0);
+    assert_eq!(safety.map[1], 2.0);
+}
+
+#[test]
+fn chasers_share_one_field_per_player_position() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(10, 10));
+    push_chaser(&mut ecs, Point::new(14, 10), None);
+    push_chaser(&mut ecs, Point::new(10, 14), None);
+
+    monster_turn(&mut ecs, &mut resources);
+    assert_eq!(rebuilds(&resources), 1);
+    monster_turn(&mut ecs, &mut resources);
+    assert_eq!(rebuilds(&resources), 1, "the player didn't move");
+
+    set_player_pos(&mut ecs, Point::new(9, 10));
+    monster_turn(&mut ecs, &mut resources);
+    assert_eq!(rebuilds(&resources), 2);
+}
+
+#[test]
+fn guards_share_the_field_back_to_their_post() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(60, 40));
+    let post = Point::new(10, 10);
+    let guard = Behaviour {
+        calm: Calm::Guard { leash: 3 },
+        ..Behaviour::default()
+    };
+    for pos in [Point::new(14, 10), Point::new(10, 14)] {
+        let monster = push_brain(&mut ecs, post, 5, guard);
+        *ecs.entry(monster)
+            .unwrap()
+            .get_component_mut::<Point>()
+            .unwrap() = pos;
+    }
+
+    // one field for the player, one for the post
+    monster_turn(&mut ecs, &mut resources);
+    assert_eq!(rebuilds(&resources), 2);
+    monster_turn(&mut ecs, &mut resources);
+    assert_eq!(rebuilds(&resources), 2);
+}
+
+#[test]
+fn tile_fields_are_dropped_once_there_are_too_many() {
+    let map = Map::new(20, 20);
+    let mut fields = FlowFields::default();
+    let first = Point::new(0, 0);
+    fields.toward(Goal::Tile(first), &[first], &map);
+    fields.toward(Goal::Tile(first), &[first], &map);
+    assert_eq!(fields.rebuilds(), 1);
+
+    for x in 1..40 {
+        let tile = Point::new(x % 20, x / 20);
+        fields.toward(Goal::Tile(tile), &[tile], &map);
+    }
+    fields.toward(Goal::Tile(first), &[first], &map);
+    assert_eq!(fields.rebuilds(), 41);
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::*;
+use dungeoncrawl::prelude::*;
+
+fn set_player_health(ecs: &mut World, current: i32) {
+    <&mut Health>::query()
+        .filter(component::<Player>())
+        .iter_mut(ecs)
+        .for_each(|health| health.current = current);
+}
+
+#[test]
+fn healing_potion_heals_and_is_used_up() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(10, 10));
+    let player = player_entity(&ecs);
+    set_player_health(&mut ecs, 50);
+    let potion = ecs.push((Item, ProvidesHealing { amount: 6 }, Carried(player)));
+    ecs.push((
+        (),
+        ActivateItem {
+            used_by: player,
+            item: potion,
+        },
+    ));
+
+    run_player_turn(&mut ecs, &mut resources);
+
+    assert_eq!(health(&ecs, player).current, 56);
+    assert!(!ecs.contains(potion));
+}
+
+#[test]
+fn healing_is_capped_at_max_health() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(10, 10));
+    let player = player_entity(&ecs);
+    set_player_health(&mut ecs, 98);
+    let potion = ecs.push((Item, ProvidesHealing { amount: 6 }, Carried(player)));
+    ecs.push((
+        (),
+        ActivateItem {
+            used_by: player,
+            item: potion,
+        },
+    ));
+
+    run_player_turn(&mut ecs, &mut resources);
+
+    let hp = health(&ecs, player);
+    assert_eq!(hp.current, hp.max);
+}
+
+#[test]
+fn dungeon_map_reveals_every_tile() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(10, 10));
+    let player = player_entity(&ecs);
+    let map_item = ecs.push((Item, ProvidesDungeonMap, Carried(player)));
+    ecs.push((
+        (),
+        ActivateItem {
+            used_by: player,
+            item: map_item,
+        },
+    ));
+
+    run_player_turn(&mut ecs, &mut resources);
+
+    let map = resources.get::<Map>().unwrap();
+    assert!(map.revealed_tiles.iter().all(|revealed| *revealed));
+}
+
+#[test]
+fn pickup_action_carries_item_and_ends_input() {
+    let (mut ecs, mut resources) = test_world();
+    resources.insert(TurnState::AwaitingInput);
+    resources.insert(Some(Action::Pickup));
+    spawn_player(&mut ecs, Point::new(10, 10));
+    let player = player_entity(&ecs);
+    let potion = ecs.push((Item, Point::new(10, 10), ProvidesHealing { amount: 6 }));
+
+    build_input_scheduler().execute(&mut ecs, &mut resources);
+
+    let entry = ecs.entry_ref(potion).unwrap();
+    assert!(entry.get_component::<Point>().is_err());
+    assert!(entry.get_component::<Carried>().unwrap().0 == player);
+    assert_eq!(
+        *resources.get::<TurnState>().unwrap(),
+        TurnState::PlayerTurn
+    );
+}
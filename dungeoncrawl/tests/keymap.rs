@@ -0,0 +1,121 @@
+mod common;
+
+use common::*;
+use dungeoncrawl::prelude::*;
+
+fn keymap(bindings: Vec<(Action, Vec<&str>)>) -> Keymap {
+    Keymap {
+        bindings: bindings
+            .into_iter()
+            .map(|(action, keys)| (action, keys.iter().map(|k| k.to_string()).collect()))
+            .collect(),
+    }
+}
+
+fn turn_state(resources: &Resources) -> TurnState {
+    *resources.get::<TurnState>().unwrap()
+}
+
+// only the input half, so the test can see which state the action asks for
+fn handle_input(ecs: &mut World, resources: &mut Resources, action: Action) {
+    resources.insert(TurnState::AwaitingInput);
+    resources.insert(Some(action));
+    build_input_scheduler().execute(ecs, resources);
+}
+
+#[test]
+fn shipped_keymap_is_valid() {
+    let keymap = Keymap::load();
+    assert!(keymap.check().is_ok());
+    assert_eq!(keymap.action(VirtualKeyCode::G), Some(Action::Pickup));
+    // the old bindings still work
+    assert_eq!(
+        keymap.action(VirtualKeyCode::W),
+        keymap.action(VirtualKeyCode::Up)
+    );
+    assert_eq!(keymap.action(VirtualKeyCode::Key1), Some(Action::Use(0)));
+    for action in [
+        Action::Wait,
+        Action::Descend,
+        Action::Inventory,
+        Action::Help,
+        Action::Restart,
+        Action::ReplayPause,
+        Action::ReplayStep,
+        Action::ReplayFastForward,
+    ] {
+        assert_ne!(keymap.keys_for(action), "(unbound)", "{:?}", action);
+    }
+}
+
+#[test]
+fn a_key_can_only_do_one_thing() {
+    let good = keymap(vec![(Action::Pickup, vec!["G", "Comma"])]);
+    assert!(good.check().is_ok());
+    assert_eq!(good.keys_for(Action::Pickup), "G or Comma");
+
+    let clash = keymap(vec![
+        (Action::Pickup, vec!["G"]),
+        (Action::CloseDoor, vec!["C", "G"]),
+    ]);
+    let error = clash.check().unwrap_err();
+    assert!(error.contains("G is bound to both"), "{}", error);
+}
+
+#[test]
+fn bad_bindings_are_rejected() {
+    assert!(
+        keymap(vec![(Action::Pickup, vec!["Shift"])])
+            .check()
+            .is_err()
+    );
+    let twice = keymap(vec![
+        (Action::Wait, vec!["Z"]),
+        (Action::Wait, vec!["Space"]),
+    ]);
+    assert!(twice.check().is_err());
+    let leap = keymap(vec![(Action::Move(Point::new(2, 0)), vec!["L"])]);
+    assert!(leap.check().is_err());
+}
+
+#[test]
+fn help_lists_every_action_with_its_keys() {
+    let keymap = Keymap::load();
+    let lines = keymap.help_lines();
+    assert_eq!(lines.len(), keymap.bindings.len());
+    assert!(
+        lines
+            .iter()
+            .any(|l| l.starts_with("Use item 1") && l.ends_with('1'))
+    );
+}
+
+#[test]
+fn waiting_uses_the_turn() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(10, 10));
+    handle_input(&mut ecs, &mut resources, Action::Wait);
+    assert_eq!(turn_state(&resources), TurnState::PlayerTurn);
+    assert_eq!(player_pos(&ecs), Point::new(10, 10));
+
+    // screens are drawn by the front end, the dungeon doesn't notice
+    handle_input(&mut ecs, &mut resources, Action::Inventory);
+    assert_eq!(turn_state(&resources), TurnState::AwaitingInput);
+}
+
+#[test]
+fn descend_only_works_on_the_stairs() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(10, 10));
+    handle_input(&mut ecs, &mut resources, Action::Descend);
+    assert_eq!(turn_state(&resources), TurnState::AwaitingInput);
+
+    {
+        let mut map = resources.get_mut::<Map>().unwrap();
+        let idx = map.point2d_to_index(Point::new(10, 10));
+        map.tiles[idx] = TileType::Exit;
+    }
+    handle_input(&mut ecs, &mut resources, Action::Descend);
+    run_player_turn(&mut ecs, &mut resources);
+    assert_eq!(turn_state(&resources), TurnState::NextLevel);
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use common::*;
+use dungeoncrawl::prelude::*;
+
+fn new_game(seed: u64) -> Game {
+    let mut game = Game::new(Some(GameSeed(seed)));
+    // tests shouldn't leave replay files behind
+    game.recording = None;
+    game
+}
+
+fn tile_position(game: &Game, tile: TileType) -> Option<Point> {
+    let map = game.resources.get::<Map>().unwrap();
+    map.tiles
+        .iter()
+        .position(|t| *t == tile)
+        .map(|idx| map.index_to_point2d(idx))
+}
+
+fn exit_position(game: &Game) -> Option<Point> {
+    tile_position(game, TileType::Exit)
+}
+
+// move the player onto the stairs and let the turn finish
+fn take_stairs(game: &mut Game, stairs: TileType, expected: TurnState) {
+    let destination = tile_position(game, stairs).expect("level has no stairs");
+    let player = player_entity(&game.ecs);
+    game.ecs.push((
+        (),
+        WantsToMove {
+            entity: player,
+            destination,
+        },
+    ));
+    game.resources.insert(TurnState::PlayerTurn);
+    game.tick(None);
+    assert_eq!(game.turn_state(), expected);
+    game.tick(None);
+}
+
+fn take_exit(game: &mut Game) {
+    take_stairs(game, TileType::Exit, TurnState::NextLevel);
+}
+
+fn enemy_positions(game: &Game) -> Vec<Point> {
+    let mut positions: Vec<Point> = <&Point>::query()
+        .filter(component::<Enemy>())
+        .iter(&game.ecs)
+        .copied()
+        .collect();
+    positions.sort_by_key(|p| (p.x, p.y));
+    positions
+}
+
+#[test]
+fn exit_leads_to_next_level() {
+    let mut game = new_game(42);
+    assert_eq!(map_level(&game.ecs), 0);
+
+    take_exit(&mut game);
+
+    assert_eq!(game.turn_state(), TurnState::AwaitingInput);
+    assert_eq!(map_level(&game.ecs), 1);
+}
+
+#[test]
+fn carried_items_survive_level_change() {
+    let mut game = new_game(42);
+    let player = player_entity(&game.ecs);
+    let sword = game.ecs.push((Item, Weapon, Damage(2), Carried(player)));
+
+    take_exit(&mut game);
+
+    assert!(game.ecs.contains(sword));
+    assert!(
+        <&Enemy>::query().iter(&game.ecs).count() > 0,
+        "new level should have fresh monsters"
+    );
+}
+
+#[test]
+fn stairs_up_return_to_the_floor_as_it_was_left() {
+    let mut game = new_game(42);
+    let first_tiles = game.resources.get::<Map>().unwrap().tiles.clone();
+    let first_enemies = enemy_positions(&game);
+    let exit = exit_position(&game).unwrap();
+
+    take_exit(&mut game);
+    assert!(tile_position(&game, TileType::UpStairs).is_some());
+    take_stairs(&mut game, TileType::UpStairs, TurnState::PreviousLevel);
+
+    assert_eq!(game.turn_state(), TurnState::AwaitingInput);
+    assert_eq!(map_level(&game.ecs), 0);
+    assert_eq!(player_pos(&game.ecs), exit);
+    assert!(game.resources.get::<Map>().unwrap().tiles == first_tiles);
+    assert_eq!(enemy_positions(&game), first_enemies);
+}
+
+#[test]
+fn revisited_floor_is_not_regenerated() {
+    let mut game = new_game(42);
+    take_exit(&mut game);
+    let second_tiles = game.resources.get::<Map>().unwrap().tiles.clone();
+    let second_enemies = enemy_positions(&game);
+
+    take_stairs(&mut game, TileType::UpStairs, TurnState::PreviousLevel);
+    take_exit(&mut game);
+
+    assert_eq!(map_level(&game.ecs), 1);
+    assert!(game.resources.get::<Map>().unwrap().tiles == second_tiles);
+    assert_eq!(enemy_positions(&game), second_enemies);
+}
+
+#[test]
+fn last_level_has_grail_instead_of_exit() {
+    let mut game = new_game(7);
+    take_exit(&mut game);
+    take_exit(&mut game);
+
+    assert_eq!(map_level(&game.ecs), 2);
+    assert!(exit_position(&game).is_none());
+    assert_eq!(<&Grail>::query().iter(&game.ecs).count(), 1);
+}
+
+#[test]
+fn reaching_grail_is_victory() {
+    let mut game = new_game(7);
+    let grail = Point::new(5, 5);
+    spawn_grail(&mut game.ecs, grail);
+    set_player_pos(&mut game.ecs, grail);
+    game.resources.insert(TurnState::PlayerTurn);
+
+    game.tick(None);
+
+    assert_eq!(game.turn_state(), TurnState::Victory);
+}
+
+#[test]
+fn zero_health_is_game_over() {
+    let mut game = new_game(7);
+    <&mut Health>::query()
+        .filter(component::<Player>())
+        .iter_mut(&mut game.ecs)
+        .for_each(|health| health.current = 0);
+    game.resources.insert(TurnState::PlayerTurn);
+
+    game.tick(None);
+
+    assert_eq!(game.turn_state(), TurnState::GameOver);
+}
+
+#[test]
+fn simulations_with_same_seed_match() {
+    let campaign = Campaign::load();
+    let first = simulate(GameSeed(3), &campaign, &mut RandomActions::new(3), 300);
+    let second = simulate(GameSeed(3), &campaign, &mut RandomActions::new(3), 300);
+    assert_eq!(first.outcome, second.outcome);
+    assert_eq!(first.turns, second.turns);
+    assert_eq!(first.map_level, second.map_level);
+}
@@ -0,0 +1,306 @@
+use dungeoncrawl::prelude::*;
+use std::sync::Arc;
+
+const SEEDS: u64 = 20;
+
+fn distances_from(map: &Map, start: Point) -> DijkstraMap {
+    DijkstraMap::new(
+        map.width,
+        map.height,
+        &[map.point2d_to_index(start)],
+        // the player opens doors on the way
+        &map.with_doors_open(),
+        1024.0,
+    )
+}
+
+#[test]
+fn grail_is_reachable_from_player_start() {
+    let prefabs = Arc::new(Prefabs::load());
+    for seed in 0..SEEDS {
+        let mb = MapBuilder::new(
+            &mut GameSeed(seed).level_rng(0),
+            MAP_WIDTH,
+            MAP_HEIGHT,
+            &prefabs,
+        );
+        let dijkstra_map = distances_from(&mb.map, mb.player_start);
+        let grail_idx = mb.map.point2d_to_index(mb.grail_start);
+        assert!(
+            dijkstra_map.map[grail_idx] < f32::MAX,
+            "seed {}: grail at {:?} unreachable from {:?}",
+            seed,
+            mb.grail_start,
+            mb.player_start
+        );
+    }
+}
+
+#[test]
+fn player_and_monsters_start_on_floor() {
+    let prefabs = Arc::new(Prefabs::load());
+    for seed in 0..SEEDS {
+        let mb = MapBuilder::new(
+            &mut GameSeed(seed).level_rng(0),
+            MAP_WIDTH,
+            MAP_HEIGHT,
+            &prefabs,
+        );
+        assert!(mb.map.can_enter_tile(mb.player_start), "seed {}", seed);
+        for spawn in mb.monster_spawns.iter() {
+            assert!(
+                mb.map.can_enter_tile(*spawn),
+                "seed {}: monster spawn {:?} in a wall",
+                seed,
+                spawn
+            );
+        }
+    }
+}
+
+#[test]
+fn same_seed_builds_same_map() {
+    let prefabs = Arc::new(Prefabs::load());
+    for seed in 0..SEEDS {
+        let first = MapBuilder::new(
+            &mut GameSeed(seed).level_rng(0),
+            MAP_WIDTH,
+            MAP_HEIGHT,
+            &prefabs,
+        );
+        let second = MapBuilder::new(
+            &mut GameSeed(seed).level_rng(0),
+            MAP_WIDTH,
+            MAP_HEIGHT,
+            &prefabs,
+        );
+        assert!(first.map.tiles == second.map.tiles, "seed {}", seed);
+        assert_eq!(first.player_start, second.player_start);
+        assert_eq!(first.monster_spawns, second.monster_spawns);
+    }
+}
+
+#[test]
+fn maps_can_be_smaller_or_larger_than_the_screen() {
+    let prefabs = Arc::new(Prefabs::load());
+    for (width, height) in [(40, 30), (160, 100)] {
+        for seed in 0..5 {
+            let mb = MapBuilder::new(&mut GameSeed(seed).level_rng(0), width, height, &prefabs);
+            assert_eq!(mb.map.tiles.len(), (width * height) as usize);
+            assert!(mb.map.in_bounds(mb.player_start));
+            assert!(mb.map.in_bounds(mb.grail_start));
+            let dijkstra_map = distances_from(&mb.map, mb.player_start);
+            let grail_idx = mb.map.point2d_to_index(mb.grail_start);
+            assert!(
+                dijkstra_map.map[grail_idx] < f32::MAX,
+                "{}x{} seed {}",
+                width,
+                height,
+                seed
+            );
+        }
+    }
+}
+
+#[test]
+fn every_architect_copes_with_tiny_maps() {
+    let prefabs = Arc::new(Prefabs::load());
+    let registry = ArchitectRegistry::standard();
+    for name in registry.names() {
+        for size in [MIN_FLOOR_SIZE, 11, 12] {
+            let floor = Floor {
+                architects: vec![(name.to_string(), 1)],
+                ..Floor::any(size, size)
+            };
+            for seed in 0..5 {
+                let mb = MapBuilder::for_floor(&mut GameSeed(seed).level_rng(0), &floor, &prefabs);
+                assert!(
+                    mb.map.in_bounds(mb.player_start) && mb.map.in_bounds(mb.grail_start),
+                    "{} at {}x{} seed {}",
+                    name,
+                    size,
+                    size,
+                    seed
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn every_registered_architect_builds_a_playable_map() {
+    let prefabs = Arc::new(Prefabs::load());
+    let registry = ArchitectRegistry::standard();
+    for name in registry.names() {
+        let floor = Floor {
+            architects: vec![(name.to_string(), 1)],
+            ..Floor::any(MAP_WIDTH, MAP_HEIGHT)
+        };
+        for seed in 0..5 {
+            let mb = MapBuilder::for_floor(&mut GameSeed(seed).level_rng(0), &floor, &prefabs);
+            assert!(
+                mb.map.can_enter_tile(mb.player_start),
+                "{} seed {}",
+                name,
+                seed
+            );
+            let dijkstra_map = distances_from(&mb.map, mb.player_start);
+            let grail_idx = mb.map.point2d_to_index(mb.grail_start);
+            assert!(
+                dijkstra_map.map[grail_idx] < f32::MAX,
+                "{} seed {}",
+                name,
+                seed
+            );
+        }
+    }
+}
+
+// a corridor along the top row, easy to recognise
+struct CorridorArchitect {}
+
+impl MapArchitect for CorridorArchitect {
+    fn new(&mut self, _rng: &mut RandomNumberGenerator, width: i32, height: i32) -> MapBuilder {
+        let mut mb = MapBuilder::blank(width, height);
+        mb.fill(TileType::Wall);
+        for x in 0..width {
+            let idx = mb.map.idx(x, 0);
+            mb.map.tiles[idx] = TileType::Floor;
+        }
+        mb.player_start = Point::new(0, 0);
+        mb.grail_start = Point::new(width - 1, 0);
+        mb
+    }
+}
+
+#[test]
+fn new_and_default_registries_hold_the_standard_architects() {
+    let standard = ArchitectRegistry::standard().names();
+    assert_eq!(ArchitectRegistry::new().names(), standard);
+    assert_eq!(ArchitectRegistry::default().names(), standard);
+    assert!(ArchitectRegistry::empty().names().is_empty());
+}
+
+#[test]
+fn custom_architects_can_be_registered_and_weighted() {
+    let prefabs = Arc::new(Prefabs::load());
+    let mut registry = ArchitectRegistry::standard();
+    registry.register("corridor", 0, || Box::new(CorridorArchitect {}));
+    assert!(registry.contains("corridor"));
+    // no default weight, so it stays out of the normal mix
+    assert!(
+        registry
+            .default_weights()
+            .iter()
+            .all(|(name, _)| name != "corridor")
+    );
+
+    let floor = Floor {
+        architects: vec![("rooms".to_string(), 0), ("corridor".to_string(), 3)],
+        ..Floor::any(MAP_WIDTH, MAP_HEIGHT)
+    };
+    for seed in 0..5 {
+        let mb = MapBuilder::with_registry(
+            &mut GameSeed(seed).level_rng(0),
+            &floor,
+            &registry,
+            &prefabs,
+        );
+        // the prefab may be stamped on top, but the ends of the corridor are left alone
+        assert_eq!(mb.player_start, Point::new(0, 0));
+        assert_eq!(mb.grail_start, Point::new(MAP_WIDTH - 1, 0));
+    }
+}
+
+#[test]
+fn bsp_rooms_are_all_connected() {
+    let prefabs = Arc::new(Prefabs::load());
+    let floor = Floor {
+        architects: vec![("bsp".to_string(), 1)],
+        ..Floor::any(MAP_WIDTH, MAP_HEIGHT)
+    };
+    for seed in 0..SEEDS {
+        let mb = MapBuilder::for_floor(&mut GameSeed(seed).level_rng(0), &floor, &prefabs);
+        assert!(
+            mb.rooms.len() > 4,
+            "seed {}: only {} rooms",
+            seed,
+            mb.rooms.len()
+        );
+        let dijkstra_map = distances_from(&mb.map, mb.player_start);
+        // the prefab may have put a wall over a room centre
+        for room in mb
+            .rooms
+            .iter()
+            .filter(|r| mb.map.can_enter_tile(r.center()))
+        {
+            let idx = mb.map.point2d_to_index(room.center());
+            assert!(
+                dijkstra_map.map[idx] < f32::MAX,
+                "seed {}: room at {:?} cut off",
+                seed,
+                room.center()
+            );
+        }
+    }
+}
+
+#[test]
+fn voronoi_floor_is_one_connected_region() {
+    let prefabs = Arc::new(Prefabs::load());
+    let floor = Floor {
+        architects: vec![("voronoi".to_string(), 1)],
+        ..Floor::any(MAP_WIDTH, MAP_HEIGHT)
+    };
+    for seed in 0..SEEDS {
+        let mb = MapBuilder::for_floor(&mut GameSeed(seed).level_rng(0), &floor, &prefabs);
+        let dijkstra_map = distances_from(&mb.map, mb.player_start);
+        let floor_tiles: Vec<usize> = mb
+            .map
+            .tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| **t == TileType::Floor)
+            .map(|(idx, _)| idx)
+            .collect();
+        assert!(
+            floor_tiles.len() > mb.map.tiles.len() / 3,
+            "seed {}: cells mostly walled up",
+            seed
+        );
+        assert!(
+            floor_tiles
+                .iter()
+                .all(|idx| dijkstra_map.map[*idx] < f32::MAX),
+            "seed {}: unreachable floor",
+            seed
+        );
+    }
+}
+
+#[test]
+fn wfc_builds_connected_maps_from_the_samples() {
+    let prefabs = Arc::new(Prefabs::load());
+    let floor = Floor {
+        architects: vec![("wfc".to_string(), 1)],
+        ..Floor::any(MAP_WIDTH, MAP_HEIGHT)
+    };
+    for seed in 0..5 {
+        let mb = MapBuilder::for_floor(&mut GameSeed(seed).level_rng(0), &floor, &prefabs);
+        let dijkstra_map = distances_from(&mb.map, mb.player_start);
+        // doors are added on top, the player walks through them too
+        let open_tiles = mb
+            .map
+            .tiles
+            .iter()
+            .filter(|t| t.is_walkable() || matches!(t, TileType::Door | TileType::LockedDoor))
+            .count();
+        let reachable = dijkstra_map.map.iter().filter(|d| **d < f32::MAX).count();
+        assert!(
+            open_tiles > 0 && open_tiles < mb.map.tiles.len(),
+            "seed {}",
+            seed
+        );
+        assert_eq!(reachable, open_tiles, "seed {}: unreachable floor", seed);
+    }
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use common::*;
+use dungeoncrawl::prelude::*;
+
+fn want_move(ecs: &mut World, entity: Entity, destination: Point) {
+    ecs.push((
+        (),
+        WantsToMove {
+            entity,
+            destination,
+        },
+    ));
+}
+
+fn behaviour(calm: Calm) -> Behaviour {
+    Behaviour {
+        calm,
+        ..Behaviour::default()
+    }
+}
+
+#[test]
+fn the_first_move_onto_a_tile_wins() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(20, 20));
+    let first = push_monster(&mut ecs, Point::new(5, 5), 5, 1);
+    let second = push_monster(&mut ecs, Point::new(7, 5), 5, 1);
+    want_move(&mut ecs, first, Point::new(6, 5));
+    want_move(&mut ecs, second, Point::new(6, 5));
+
+    run_player_turn(&mut ecs, &mut resources);
+
+    assert_eq!(position(&ecs, first), Point::new(6, 5));
+    assert_eq!(position(&ecs, second), Point::new(7, 5));
+    assert_eq!(<&WantsToMove>::query().iter(&ecs).count(), 0);
+}
+
+#[test]
+fn moves_into_tiles_being_left_go_through() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(20, 20));
+    let behind = push_monster(&mut ecs, Point::new(5, 5), 5, 1);
+    let ahead = push_monster(&mut ecs, Point::new(6, 5), 5, 1);
+    // queued the wrong way round, the one behind still follows on
+    want_move(&mut ecs, behind, Point::new(6, 5));
+    want_move(&mut ecs, ahead, Point::new(7, 5));
+
+    run_player_turn(&mut ecs, &mut resources);
+
+    assert_eq!(position(&ecs, behind), Point::new(6, 5));
+    assert_eq!(position(&ecs, ahead), Point::new(7, 5));
+}
+
+#[test]
+fn swapping_places_is_blocked() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(20, 20));
+    let left = push_monster(&mut ecs, Point::new(5, 5), 5, 1);
+    let right = push_monster(&mut ecs, Point::new(6, 5), 5, 1);
+    want_move(&mut ecs, left, Point::new(6, 5));
+    want_move(&mut ecs, right, Point::new(5, 5));
+
+    run_player_turn(&mut ecs, &mut resources);
+
+    assert_eq!(position(&ecs, left), Point::new(5, 5));
+    assert_eq!(position(&ecs, right), Point::new(6, 5));
+}
+
+#[test]
+fn one_step_a_turn() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(20, 20));
+    let monster = push_monster(&mut ecs, Point::new(5, 5), 5, 1);
+    want_move(&mut ecs, monster, Point::new(6, 5));
+    want_move(&mut ecs, monster, Point::new(4, 5));
+
+    run_player_turn(&mut ecs, &mut resources);
+
+    assert_eq!(position(&ecs, monster), Point::new(6, 5));
+}
+
+#[test]
+fn wanderers_never_share_a_tile() {
+    let (mut ecs, mut resources) = test_world();
+    // a small room so they keep bumping into each other
+    {
+        let mut map = resources.get_mut::<Map>().unwrap();
+        map.tiles.iter_mut().for_each(|t| *t = TileType::Wall);
+        for y in 5..8 {
+            for x in 5..8 {
+                let idx = map.point2d_to_index(Point::new(x, y));
+                map.tiles[idx] = TileType::Floor;
+            }
+        }
+    }
+    spawn_player(&mut ecs, Point::new(40, 40));
+    for x in 5..8 {
+        push_brain(&mut ecs, Point::new(x, 6), 5, behaviour(Calm::Wander));
+    }
+
+    for _ in 0..50 {
+        monster_turn(&mut ecs, &mut resources);
+        let mut tiles: Vec<Point> = <&Point>::query()
+            .filter(component::<Brain>())
+            .iter(&ecs)
+            .copied()
+            .collect();
+        tiles.sort_by_key(|p| (p.x, p.y));
+        tiles.dedup();
+        assert_eq!(tiles.len(), 3);
+    }
+}
+
+#[test]
+fn chasers_step_round_each_other() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(10, 10));
+    let front = push_brain(&mut ecs, Point::new(11, 11), 5, behaviour(Calm::Idle));
+    let back = push_brain(&mut ecs, Point::new(12, 11), 5, behaviour(Calm::Idle));
+
+    monster_turn(&mut ecs, &mut resources);
+
+    // the way past the front chaser is still occupied, so it goes over the top
+    assert_ne!(position(&ecs, front), Point::new(11, 11));
+    assert_eq!(position(&ecs, back), Point::new(12, 10));
+}
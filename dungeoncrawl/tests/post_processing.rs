@@ -0,0 +1,117 @@
+use dungeoncrawl::prelude::*;
+use std::sync::Arc;
+
+fn build(architect: &str, steps: Vec<PostStep>, seed: u64) -> MapBuilder {
+    let prefabs = Arc::new(Prefabs::load());
+    let floor = Floor {
+        architects: vec![(architect.to_string(), 1)],
+        post_processing: steps,
+        ..Floor::any(MAP_WIDTH, MAP_HEIGHT)
+    };
+    MapBuilder::for_floor(&mut GameSeed(seed).level_rng(0), &floor, &prefabs)
+}
+
+fn reachable(map: &Map, start: Point) -> DijkstraMap {
+    DijkstraMap::new(
+        map.width,
+        map.height,
+        &[map.point2d_to_index(start)],
+        // the player opens doors on the way
+        &map.with_doors_open(),
+        1024.0,
+    )
+}
+
+#[test]
+fn horizontal_mirror_makes_both_halves_match() {
+    for seed in 0..5 {
+        let mb = build(
+            "automata",
+            vec![PostStep::Mirror(Symmetry::Horizontal)],
+            seed,
+        );
+        let map = &mb.map;
+        for y in 0..map.height {
+            for x in 0..map.width {
+                let mirrored = map.idx(map.width - 1 - x, y);
+                assert!(
+                    map.tiles[map.idx(x, y)] == map.tiles[mirrored],
+                    "seed {}: ({}, {}) differs",
+                    seed,
+                    x,
+                    y
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn doors_go_in_gaps_around_rooms() {
+    let mut doors = 0;
+    for seed in 0..5 {
+        let mb = build("rooms", vec![PostStep::AddDoors], seed);
+        for (idx, tile) in mb.map.tiles.iter().enumerate() {
+            if *tile != TileType::Door {
+                continue;
+            }
+            doors += 1;
+            let p = mb.map.index_to_point2d(idx);
+            let wall = |x, y| mb.map.tiles[mb.map.idx(x, y)] == TileType::Wall;
+            assert!(
+                (wall(p.x - 1, p.y) && wall(p.x + 1, p.y))
+                    || (wall(p.x, p.y - 1) && wall(p.x, p.y + 1)),
+                "seed {}: door at {:?} isn't in a gap",
+                seed,
+                p
+            );
+        }
+        // doors never cut the level in two
+        let dijkstra_map = reachable(&mb.map, mb.player_start);
+        assert!(dijkstra_map.map[mb.map.point2d_to_index(mb.grail_start)] < f32::MAX);
+    }
+    assert!(doors > 0, "no doors placed on any rooms map");
+}
+
+#[test]
+fn cull_and_recompute_leave_one_connected_level() {
+    let steps = vec![
+        PostStep::Mirror(Symmetry::Both),
+        PostStep::SmoothWalls,
+        PostStep::WidenCorridors,
+        PostStep::CullUnreachable,
+        PostStep::RecomputeStarts,
+    ];
+    for seed in 0..5 {
+        let mb = build("drunkard", steps.clone(), seed);
+        assert!(mb.map.can_enter_tile(mb.player_start), "seed {}", seed);
+        let dijkstra_map = reachable(&mb.map, mb.player_start);
+        for (idx, tile) in mb.map.tiles.iter().enumerate() {
+            if *tile == TileType::Floor {
+                assert!(dijkstra_map.map[idx] < f32::MAX, "seed {}", seed);
+            }
+        }
+        for spawn in mb.monster_spawns.iter() {
+            assert!(dijkstra_map.map[mb.map.point2d_to_index(*spawn)] < f32::MAX);
+        }
+    }
+}
+
+#[test]
+fn steps_run_in_the_order_given() {
+    // widening first and mirroring last still leaves a symmetric map
+    let mirrored_last = build(
+        "rooms",
+        vec![
+            PostStep::WidenCorridors,
+            PostStep::Mirror(Symmetry::Vertical),
+        ],
+        3,
+    );
+    let map = &mirrored_last.map;
+    for y in 0..map.height {
+        for x in 0..map.width {
+            assert!(map.tiles[map.idx(x, y)] == map.tiles[map.idx(x, map.height - 1 - y)]);
+        }
+    }
+}
@@ -0,0 +1,202 @@
+mod common;
+
+use common::*;
+use dungeoncrawl::prelude::*;
+use std::sync::Arc;
+
+fn build(architect: &str, steps: Vec<PostStep>, seed: u64) -> MapBuilder {
+    let prefabs = Arc::new(Prefabs::load());
+    let floor = Floor {
+        architects: vec![(architect.to_string(), 1)],
+        post_processing: steps,
+        ..Floor::any(MAP_WIDTH, MAP_HEIGHT)
+    };
+    MapBuilder::for_floor(&mut GameSeed(seed).level_rng(0), &floor, &prefabs)
+}
+
+fn one_prefab(layout: &str, legend: Vec<(char, LegendEntry)>) -> Prefabs {
+    Prefabs {
+        legend: vec![
+            ('-', LegendEntry::Floor),
+            ('#', LegendEntry::Wall),
+            ('M', LegendEntry::Spawn),
+        ],
+        prefabs: vec![Prefab {
+            name: "test".to_string(),
+            kind: PrefabKind::Vault,
+            layout: layout.to_string(),
+            legend,
+            min_level: 0,
+            max_level: None,
+            frequency: 1,
+            rotate: false,
+            mirror: false,
+            spawns: vec![],
+        }],
+    }
+}
+
+#[test]
+fn shipped_prefabs_are_valid() {
+    let prefabs = Prefabs::load();
+    assert!(prefabs.check(&Templates::load()).is_ok());
+    for kind in [PrefabKind::Level, PrefabKind::Vault, PrefabKind::Sectional] {
+        assert!(
+            prefabs.prefabs.iter().any(|p| p.kind == kind),
+            "no {:?} prefab",
+            kind
+        );
+    }
+}
+
+#[test]
+fn unknown_characters_and_entities_are_rejected() {
+    let templates = Templates::load();
+    assert!(one_prefab("-#-", vec![]).check(&templates).is_ok());
+    assert!(one_prefab("-?-", vec![]).check(&templates).is_err());
+    let dragon = vec![('D', LegendEntry::Entity("Dragon".to_string()))];
+    assert!(one_prefab("-D-", dragon).check(&templates).is_err());
+}
+
+#[test]
+fn stamping_an_unknown_character_leaves_the_map_alone() {
+    let prefabs = one_prefab("#?#", vec![]);
+    let prefab = &prefabs.prefabs[0];
+    let mut mb = MapBuilder::blank(20, 20);
+    let mut rng = GameSeed(1).level_rng(0);
+    let stamped = prefabs.stamp(&mut mb, prefab, &prefab.rows(), Point::new(5, 5), &mut rng);
+    assert!(stamped.is_err());
+    assert!(mb.map.tiles.iter().all(|t| *t == TileType::Floor));
+}
+
+#[test]
+fn spawn_tables_name_real_templates() {
+    let templates = Templates::load();
+    let mut prefabs = one_prefab("-M-", vec![]);
+    prefabs.prefabs[0].spawns = vec![("Orc".to_string(), 1)];
+    assert!(prefabs.check(&templates).is_ok());
+    prefabs.prefabs[0].spawns = vec![("Dragon".to_string(), 1)];
+    assert!(prefabs.check(&templates).is_err());
+    prefabs.prefabs[0].spawns = vec![("Orc".to_string(), 0)];
+    assert!(prefabs.check(&templates).is_err());
+}
+
+#[test]
+fn spawns_come_from_the_prefab_table() {
+    let mut prefabs = one_prefab("-M-", vec![]);
+    prefabs.prefabs[0].spawns = vec![("Orc".to_string(), 1)];
+    let prefab = &prefabs.prefabs[0];
+    let mut mb = MapBuilder::blank(20, 20);
+    let mut rng = GameSeed(1).level_rng(0);
+    prefabs
+        .stamp(&mut mb, prefab, &prefab.rows(), Point::new(5, 5), &mut rng)
+        .unwrap();
+    assert_eq!(
+        mb.entity_spawns,
+        vec![(Point::new(6, 5), "Orc".to_string())]
+    );
+    assert!(mb.monster_spawns.is_empty());
+
+    // without a table M is an ordinary spawn for the level
+    prefabs.prefabs[0].spawns.clear();
+    let prefab = &prefabs.prefabs[0];
+    let mut mb = MapBuilder::blank(20, 20);
+    prefabs
+        .stamp(&mut mb, prefab, &prefab.rows(), Point::new(5, 5), &mut rng)
+        .unwrap();
+    assert!(mb.entity_spawns.is_empty());
+    assert_eq!(mb.monster_spawns, vec![Point::new(6, 5)]);
+}
+
+#[test]
+fn room_levels_get_themed_vaults() {
+    let vault_spawns: Vec<String> = Prefabs::load()
+        .prefabs
+        .iter()
+        .filter(|p| p.kind == PrefabKind::Vault)
+        .flat_map(|p| p.spawns.iter().map(|(name, _)| name.clone()))
+        .collect();
+    let mut themed = 0;
+    for seed in 0..10 {
+        let mb = build("rooms", default_post_processing(), seed);
+        themed += mb
+            .entity_spawns
+            .iter()
+            .filter(|(_, name)| vault_spawns.contains(name))
+            .count();
+    }
+    assert!(themed > 0, "no vault spawned anything from its table");
+}
+
+#[test]
+fn prefab_architect_builds_a_whole_level() {
+    for seed in 0..5 {
+        let mb = build("prefab", vec![], seed);
+        let at = Point::new((MAP_WIDTH - 25) / 2, (MAP_HEIGHT - 15) / 2);
+        // the crossroads may be mirrored, but @ and > stay on their rows
+        assert_eq!(mb.player_start.y, at.y + 7, "seed {}", seed);
+        assert_eq!(mb.grail_start.y, at.y + 13, "seed {}", seed);
+        assert!(mb.map.tiles[mb.map.point2d_to_index(mb.player_start)] == TileType::Floor);
+        // everything outside the layout is solid
+        assert!(mb.map.tiles[mb.map.idx(0, 0)] == TileType::Wall);
+        assert!(
+            mb.entity_spawns
+                .iter()
+                .any(|(_, name)| name == "Spike Trap")
+        );
+    }
+}
+
+#[test]
+fn vaults_place_their_entities_inside_rooms() {
+    let mut placed = 0;
+    for seed in 0..10 {
+        let mb = build("rooms", vec![PostStep::Vaults], seed);
+        for (pos, _) in mb.entity_spawns.iter() {
+            placed += 1;
+            assert!(
+                mb.rooms.iter().any(|room| room.point_in_rect(*pos)),
+                "seed {}: {:?} is outside every room",
+                seed,
+                pos
+            );
+        }
+    }
+    assert!(placed > 0, "no vault was placed");
+}
+
+#[test]
+fn vaults_with_unknown_characters_are_reported() {
+    let prefabs = Arc::new(one_prefab("#?#", vec![]));
+    let floor = Floor {
+        architects: vec![("rooms".to_string(), 1)],
+        post_processing: vec![PostStep::Vaults],
+        ..Floor::any(MAP_WIDTH, MAP_HEIGHT)
+    };
+    let reported = (0..10).any(|seed| {
+        let mb = MapBuilder::for_floor(&mut GameSeed(seed).level_rng(0), &floor, &prefabs);
+        matches!(mb.build_error, Some(MapError::BadPrefab(_)))
+    });
+    assert!(reported, "the broken vault was dropped silently");
+}
+
+#[test]
+fn trap_hurts_the_player_once() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(10, 10));
+    let player = player_entity(&ecs);
+    ecs.push((Trap, Point::new(11, 10), Damage(3)));
+    ecs.push((
+        (),
+        WantsToMove {
+            entity: player,
+            destination: Point::new(11, 10),
+        },
+    ));
+
+    run_player_turn(&mut ecs, &mut resources);
+
+    assert_eq!(health(&ecs, player).current, 97);
+    let traps = <&Trap>::query().iter(&ecs).count();
+    assert_eq!(traps, 0, "a sprung trap is used up");
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use common::*;
+use dungeoncrawl::prelude::*;
+
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir()
+        .join(format!("dungeoncrawl_{}_{}", std::process::id(), name))
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[test]
+fn saved_game_restores_identically() {
+    let game = Game::new(Some(GameSeed(11)));
+    let path = temp_path("roundtrip.ron");
+    SaveGame::capture(&game.ecs, &game.resources)
+        .unwrap()
+        .write(&path)
+        .unwrap();
+
+    let mut ecs = World::default();
+    let mut resources = Resources::default();
+    SaveGame::read(&path)
+        .unwrap()
+        .restore(&mut ecs, &mut resources)
+        .unwrap();
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(player_pos(&ecs), player_pos(&game.ecs));
+    assert_eq!(
+        <&Enemy>::query().iter(&ecs).count(),
+        <&Enemy>::query().iter(&game.ecs).count()
+    );
+    // monsters keep what they were doing
+    let restored: Vec<Brain> = <&Brain>::query().iter(&ecs).copied().collect();
+    assert!(
+        <&Brain>::query()
+            .iter(&game.ecs)
+            .all(|b| restored.contains(b))
+    );
+    assert!(resources.get::<Map>().unwrap().tiles == game.resources.get::<Map>().unwrap().tiles);
+    assert_eq!(*resources.get::<GameSeed>().unwrap(), GameSeed(11));
+}
+
+#[test]
+fn other_save_versions_are_refused() {
+    let game = Game::new(Some(GameSeed(11)));
+    let path = temp_path("old_version.ron");
+    let mut save = SaveGame::capture(&game.ecs, &game.resources).unwrap();
+    save.version = SAVE_VERSION + 1;
+    save.write(&path).unwrap();
+
+    let result = SaveGame::read(&path);
+    std::fs::remove_file(&path).ok();
+
+    assert!(matches!(result, Err(SaveError::IncompatibleVersion { .. })));
+}
+
+// the player stands still while the monsters wander and chase
+fn wait_turns(game: &mut Game, turns: usize) {
+    for _ in 0..turns {
+        if game.turn_state() != TurnState::AwaitingInput {
+            break;
+        }
+        game.tick(Some(Action::Wait));
+        while !matches!(
+            game.turn_state(),
+            TurnState::AwaitingInput | TurnState::GameOver
+        ) {
+            game.tick(None);
+        }
+    }
+}
+
+fn positions(ecs: &World) -> Vec<Point> {
+    <&Point>::query()
+        .filter(component::<Enemy>())
+        .iter(ecs)
+        .copied()
+        .collect()
+}
+
+#[test]
+fn loading_carries_on_exactly_where_the_save_left_off() {
+    let mut game = Game::new(Some(GameSeed(11)));
+    game.recording = None;
+    wait_turns(&mut game, 10);
+    let save = SaveGame::capture(&game.ecs, &game.resources).unwrap();
+    let path = temp_path("carry_on.ron");
+    save.write(&path).unwrap();
+
+    let mut loaded = Game::new(Some(GameSeed(11)));
+    loaded.recording = None;
+    SaveGame::read(&path)
+        .unwrap()
+        .restore(&mut loaded.ecs, &mut loaded.resources)
+        .unwrap();
+    std::fs::remove_file(&path).ok();
+
+    wait_turns(&mut game, 20);
+    wait_turns(&mut loaded, 20);
+    assert_eq!(positions(&loaded.ecs), positions(&game.ecs));
+    assert_eq!(player_pos(&loaded.ecs), player_pos(&game.ecs));
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use common::*;
+use dungeoncrawl::prelude::*;
+
+// two ways from the west end to the east end, straight through lava or round the top
+fn lava_map() -> Map {
+    let mut map = Map::new(7, 4);
+    map.tiles.iter_mut().for_each(|t| *t = TileType::Wall);
+    for x in 1..6 {
+        for y in 1..3 {
+            let idx = map.point2d_to_index(Point::new(x, y));
+            map.tiles[idx] = TileType::Floor;
+        }
+    }
+    let lava = map.point2d_to_index(Point::new(3, 2));
+    map.tiles[lava] = TileType::Lava;
+    map
+}
+
+#[test]
+fn tiles_describe_how_they_are_crossed() {
+    assert!(!TileType::DeepWater.is_walkable());
+    assert!(TileType::Bridge.is_walkable());
+    assert!(TileType::ShallowWater.is_walkable());
+    assert!(TileType::ShallowWater.movement_cost() > TileType::Floor.movement_cost());
+    assert!(TileType::Rubble.movement_cost() > TileType::Floor.movement_cost());
+    assert!(TileType::Lava.hazard_damage() > 0);
+    assert_eq!(TileType::Floor.hazard_damage(), 0);
+    // you can see across water and lava
+    for tile in [TileType::ShallowWater, TileType::DeepWater, TileType::Lava] {
+        assert!(!tile.is_opaque(), "{:?}", tile);
+    }
+}
+
+#[test]
+fn monsters_path_around_lava() {
+    let map = lava_map();
+    let target = map.point2d_to_index(Point::new(5, 2));
+    let mut dijkstra_map = DijkstraMap::new(map.width, map.height, &[target], &map, 1024.0);
+    // bracket-lib leaves the start at 2.0, walking downhill has to end there
+    dijkstra_map.map[target] = 0.0;
+
+    let mut idx = map.point2d_to_index(Point::new(1, 2));
+    let mut steps = 0;
+    while idx != target {
+        idx = DijkstraMap::find_lowest_exit(&dijkstra_map, idx, &map).expect("stuck");
+        assert!(map.tiles[idx] != TileType::Lava, "walked into the lava");
+        steps += 1;
+        assert!(steps < 20);
+    }
+}
+
+#[test]
+fn deep_water_blocks_the_way() {
+    let mut map = lava_map();
+    for y in 1..3 {
+        let idx = map.point2d_to_index(Point::new(3, y));
+        map.tiles[idx] = TileType::DeepWater;
+    }
+    assert!(!map.can_enter_tile(Point::new(3, 1)));
+    let dijkstra_map = DijkstraMap::new(
+        map.width,
+        map.height,
+        &[map.point2d_to_index(Point::new(5, 2))],
+        &map,
+        1024.0,
+    );
+    assert!(dijkstra_map.map[map.point2d_to_index(Point::new(1, 2))] == f32::MAX);
+
+    // a bridge gets across again
+    let idx = map.point2d_to_index(Point::new(3, 1));
+    map.tiles[idx] = TileType::Bridge;
+    assert!(map.can_enter_tile(Point::new(3, 1)));
+}
+
+#[test]
+fn lava_burns_whoever_stands_in_it() {
+    let (mut ecs, mut resources) = test_world();
+    spawn_player(&mut ecs, Point::new(10, 10));
+    let player = player_entity(&ecs);
+    let monster = push_monster(&mut ecs, Point::new(20, 20), 1, 1);
+    {
+        let mut map = resources.get_mut::<Map>().unwrap();
+        for pos in [Point::new(10, 10), Point::new(20, 20)] {
+            let idx = map.point2d_to_index(pos);
+            map.tiles[idx] = TileType::Lava;
+        }
+    }
+
+    resources.insert(TurnState::EnemyTurn);
+    build_enemy_scheduler().execute(&mut ecs, &mut resources);
+
+    let damage = TileType::Lava.hazard_damage();
+    assert_eq!(health(&ecs, player).current, 100 - damage);
+    assert!(ecs.entry_ref(monster).is_err(), "the monster burned up");
+}
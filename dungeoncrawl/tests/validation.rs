@@ -0,0 +1,170 @@
+use dungeoncrawl::prelude::*;
+use std::sync::Arc;
+
+// two rooms with a wall between them, the start in the big one and the exit in the small one
+fn split_map() -> MapBuilder {
+    let mut mb = MapBuilder::blank(20, 10);
+    mb.fill(TileType::Wall);
+    for y in 1..9 {
+        for x in 1..19 {
+            if x != 15 {
+                let idx = mb.map.idx(x, y);
+                mb.map.tiles[idx] = TileType::Floor;
+            }
+        }
+    }
+    mb.player_start = Point::new(2, 2);
+    mb.grail_start = Point::new(17, 5);
+    mb
+}
+
+// walls everywhere, nothing an architect should ever hand back
+struct SolidArchitect {}
+
+impl MapArchitect for SolidArchitect {
+    fn new(&mut self, _rng: &mut RandomNumberGenerator, width: i32, height: i32) -> MapBuilder {
+        let mut mb = MapBuilder::blank(width, height);
+        mb.fill(TileType::Wall);
+        mb
+    }
+}
+
+#[test]
+fn every_architect_builds_a_valid_map() {
+    let prefabs = Arc::new(Prefabs::load());
+    let registry = ArchitectRegistry::standard();
+    for name in registry.names() {
+        let floor = Floor {
+            architects: vec![(name.to_string(), 1)],
+            ..Floor::any(MAP_WIDTH, MAP_HEIGHT)
+        };
+        for seed in 0..5 {
+            let mb = MapBuilder::try_build(
+                &mut GameSeed(seed).level_rng(0),
+                &floor,
+                &registry,
+                &prefabs,
+            )
+            .unwrap_or_else(|e| panic!("{} seed {}: {}", name, seed, e));
+            assert_eq!(mb.validate(), Ok(()), "{} seed {}", name, seed);
+        }
+    }
+}
+
+#[test]
+fn unreachable_exit_and_spawns_are_reported() {
+    let mut mb = split_map();
+    assert_eq!(
+        mb.validate(),
+        Err(MapError::ExitUnreachable(Point::new(17, 5)))
+    );
+
+    mb.grail_start = Point::new(10, 5);
+    mb.monster_spawns.push(Point::new(17, 2));
+    assert_eq!(
+        mb.validate(),
+        Err(MapError::SpawnUnreachable(Point::new(17, 2)))
+    );
+
+    mb.monster_spawns.clear();
+    assert_eq!(
+        mb.validate(),
+        Err(MapError::FloorUnreachable { tiles: 3 * 8 })
+    );
+}
+
+#[test]
+fn locked_doors_get_a_key_in_front_of_them() {
+    let mut mb = split_map();
+    let door = mb.map.idx(15, 5);
+    mb.map.tiles[door] = TileType::LockedDoor;
+    assert_eq!(
+        mb.validate(),
+        Err(MapError::MissingKeys { locked: 1, keys: 0 })
+    );
+
+    // a key behind the door it opens doesn't help
+    mb.entity_spawns
+        .push((Point::new(17, 2), "Key".to_string()));
+    assert_eq!(
+        mb.validate(),
+        Err(MapError::MissingKeys { locked: 1, keys: 0 })
+    );
+
+    assert_eq!(mb.repair(), Ok(()));
+    let keys: Vec<Point> = mb
+        .entity_spawns
+        .iter()
+        .filter(|(_, name)| name == "Key")
+        .map(|(p, _)| *p)
+        .collect();
+    assert_eq!(keys.len(), 2);
+    assert!(keys.iter().any(|p| p.x < 15), "no key on the start's side");
+}
+
+#[test]
+fn blocked_start_is_reported() {
+    let mut mb = split_map();
+    mb.player_start = Point::new(0, 0);
+    assert_eq!(mb.validate(), Err(MapError::StartBlocked(Point::new(0, 0))));
+    mb.player_start = Point::new(-1, 3);
+    assert_eq!(
+        mb.validate(),
+        Err(MapError::StartBlocked(Point::new(-1, 3)))
+    );
+}
+
+#[test]
+fn repair_walls_off_the_unreachable_part() {
+    let mut mb = split_map();
+    mb.monster_spawns = vec![Point::new(5, 5), Point::new(17, 2)];
+    mb.entity_spawns = vec![(Point::new(18, 8), "Spike Trap".to_string())];
+
+    assert_eq!(mb.repair(), Ok(()));
+    assert!(mb.map.tiles[mb.map.idx(17, 2)] == TileType::Wall);
+    assert_eq!(mb.monster_spawns, vec![Point::new(5, 5)]);
+    assert!(mb.entity_spawns.is_empty());
+    // the exit moved somewhere the player can get to
+    assert!(mb.grail_start.x < 15);
+    assert_eq!(mb.validate(), Ok(()));
+}
+
+#[test]
+fn repair_gives_up_when_too_little_is_left() {
+    let mut mb = split_map();
+    // start in the small room, most of the map would be walled off
+    mb.player_start = Point::new(17, 2);
+    mb.grail_start = Point::new(17, 8);
+    assert!(matches!(mb.repair(), Err(MapError::TooSmall { .. })));
+}
+
+#[test]
+fn broken_architects_are_reported_not_panicked() {
+    let prefabs = Arc::new(Prefabs::load());
+    let mut registry = ArchitectRegistry::standard();
+    registry.register("solid", 0, || Box::new(SolidArchitect {}));
+    let floor = Floor {
+        architects: vec![("solid".to_string(), 1)],
+        post_processing: vec![],
+        ..Floor::any(MAP_WIDTH, MAP_HEIGHT)
+    };
+
+    let result = MapBuilder::try_build(&mut GameSeed(1).level_rng(0), &floor, &registry, &prefabs);
+    let Err(MapError::GaveUp { last, .. }) = result else {
+        panic!("a solid map should never be accepted");
+    };
+    assert!(matches!(*last, MapError::StartBlocked(_)));
+
+    // the game still gets something playable
+    let mb = MapBuilder::with_registry(&mut GameSeed(1).level_rng(0), &floor, &registry, &prefabs);
+    assert_eq!(mb.validate(), Ok(()));
+    assert!(matches!(mb.build_error, Some(MapError::GaveUp { .. })));
+}
+
+#[test]
+fn most_distant_needs_a_start_on_the_map() {
+    let mut mb = split_map();
+    assert_eq!(mb.find_most_distant(), Some(Point::new(14, 8)));
+    mb.player_start = Point::new(-1, -1);
+    assert_eq!(mb.find_most_distant(), None);
+}